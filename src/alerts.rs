@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use notify_rust::Notification;
+
+/// Alerts kept for the panel; older ones are dropped.
+const MAX_ALERTS: usize = 50;
+/// Shortest gap between two `--notify` bells, so a burst rings once.
+const NOTIFY_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
+pub struct Alert {
+    pub at: Instant,
+    pub message: String,
+}
+
+/// Append-only log of notable events, written by the updater threads and
+/// read by the renderer.
+#[derive(Clone, Default)]
+pub struct AlertLog {
+    entries: VecDeque<Alert>,
+    /// Alerts ever pushed, including the ones since dropped.
+    total: u64,
+}
+
+impl AlertLog {
+    pub fn push(&mut self, message: String) {
+        self.entries.push_back(Alert {
+            at: Instant::now(),
+            message,
+        });
+        self.total += 1;
+        if self.entries.len() > MAX_ALERTS {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Up to `n` alerts, newest first.
+    pub fn recent(&self, n: usize) -> impl Iterator<Item = &Alert> {
+        self.entries.iter().rev().take(n)
+    }
+}
+
+/// Rings the terminal bell and raises a desktop notification for new
+/// alerts, at most once per `NOTIFY_INTERVAL`. Alerts arriving in between
+/// are only shown in the panel.
+#[derive(Default)]
+pub struct Notifier {
+    seen: u64,
+    last: Option<Instant>,
+}
+
+impl Notifier {
+    pub fn check(&mut self, log: &AlertLog) {
+        if log.total == self.seen {
+            return;
+        }
+        self.seen = log.total;
+        if self.last.is_some_and(|t| t.elapsed() < NOTIFY_INTERVAL) {
+            return;
+        }
+        self.last = Some(Instant::now());
+
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        if let Some(alert) = log.entries.back() {
+            // Best effort: no notification service just means no popup
+            let _ = Notification::new()
+                .summary("rust_hft_tui")
+                .body(&alert.message)
+                .show();
+        }
+    }
+}
+
+/// Levels a stock can carry at once (`:alert PRICE`).
+pub const MAX_LEVELS: usize = 5;
+/// How long a stock stays highlighted after crossing one.
+const LEVEL_FLASH: Duration = Duration::from_secs(5);
+
+/// A stock's user-set alert levels: a small list, since a crossing is
+/// checked against all of them on every tick.
+#[derive(Clone, Default)]
+pub struct PriceLevels {
+    levels: Vec<f64>,
+    /// The level last crossed and when, for the highlight.
+    hit: Option<(f64, Instant)>,
+}
+
+impl PriceLevels {
+    pub fn add(&mut self, level: f64) -> Result<(), String> {
+        if self.levels.contains(&level) {
+            return Err(format!("alert at {:.2} already set", level));
+        }
+        if self.levels.len() >= MAX_LEVELS {
+            return Err(format!("at most {} alerts per stock", MAX_LEVELS));
+        }
+        self.levels.push(level);
+        Ok(())
+    }
+
+    /// Whether `level` was set.
+    pub fn remove(&mut self, level: f64) -> bool {
+        let before = self.levels.len();
+        self.levels.retain(|&l| l != level);
+        self.levels.len() < before
+    }
+
+    pub fn clear(&mut self) {
+        self.levels.clear();
+        self.hit = None;
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The levels a tick from `before` to `after` went through, each with
+    /// whether it was crossed rising. Landing exactly on a level counts;
+    /// leaving one the price sat on does not, so it fires once per cross.
+    pub fn crossed(&mut self, before: f64, after: f64) -> Vec<(f64, bool)> {
+        let crossed: Vec<(f64, bool)> = self
+            .levels
+            .iter()
+            .filter_map(|&level| {
+                if before < level && after >= level {
+                    Some((level, true))
+                } else if before > level && after <= level {
+                    Some((level, false))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if let Some(&(level, _)) = crossed.last() {
+            self.hit = Some((level, Instant::now()));
+        }
+        crossed
+    }
+
+    /// The level crossed within the last `LEVEL_FLASH`, if any.
+    pub fn flashing(&self) -> Option<f64> {
+        self.hit
+            .filter(|(_, at)| at.elapsed() < LEVEL_FLASH)
+            .map(|(level, _)| level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_level_fires_once_per_cross() {
+        let mut levels = PriceLevels::default();
+        levels.add(105.0).unwrap();
+        assert_eq!(levels.crossed(104.0, 104.5), vec![]);
+        assert_eq!(levels.crossed(104.5, 105.0), vec![(105.0, true)]);
+        // Sitting on the level, then leaving it, is not another cross
+        assert_eq!(levels.crossed(105.0, 105.0), vec![]);
+        assert_eq!(levels.crossed(105.0, 106.0), vec![]);
+        assert_eq!(levels.crossed(106.0, 104.0), vec![(105.0, false)]);
+        assert_eq!(levels.flashing(), Some(105.0));
+    }
+
+    #[test]
+    fn the_list_is_small_and_unique() {
+        let mut levels = PriceLevels::default();
+        for i in 0..MAX_LEVELS {
+            levels.add(100.0 + i as f64).unwrap();
+        }
+        assert!(levels.add(100.0).is_err());
+        assert!(levels.add(200.0).is_err());
+        assert!(levels.remove(100.0));
+        assert!(!levels.remove(100.0));
+        assert_eq!(levels.len(), MAX_LEVELS - 1);
+    }
+}
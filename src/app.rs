@@ -0,0 +1,780 @@
+use crate::chart::{ChartSpec, Resolution};
+use crate::clock::Zone;
+use crate::config::Config;
+use crate::frontend::FrontendModel;
+use crate::indicators::{self, ReturnKind};
+use crate::panes::Pane;
+use crate::theme::Theme;
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Direction;
+
+/// Number of ticks shown in the short-window chart of the multi-timeframe view.
+pub const SHORT_WINDOW_LEN: usize = 20;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Overview,
+    Detail,
+    /// Every stock's candles side by side (`g`).
+    CandleGrid,
+}
+
+/// Which overview chart fills the screen; cycled with `z`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Zoom {
+    Split,
+    Backend,
+    Frontend,
+}
+
+/// Decimal places per kind of number shown, from `--*-decimals`.
+#[derive(Clone, Copy)]
+pub struct Decimals {
+    pub price: usize,
+    pub avg: usize,
+    pub indicator: usize,
+    pub percent: usize,
+}
+
+/// Unit of the latency / age readouts; cycled with `u`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Whichever of µs / ms / s keeps the number short.
+    Auto,
+    Micros,
+    Millis,
+    Secs,
+}
+
+impl TimeUnit {
+    fn next(self) -> Self {
+        match self {
+            TimeUnit::Auto => TimeUnit::Micros,
+            TimeUnit::Micros => TimeUnit::Millis,
+            TimeUnit::Millis => TimeUnit::Secs,
+            TimeUnit::Secs => TimeUnit::Auto,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeUnit::Auto => "auto",
+            TimeUnit::Micros => "µs",
+            TimeUnit::Millis => "ms",
+            TimeUnit::Secs => "s",
+        }
+    }
+}
+
+/// Which columns the backend pointer lines show; cycled with `i`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IndicatorPage {
+    /// Every built-in column plus the custom indicators.
+    All,
+    Basic,
+    Momentum,
+    Volatility,
+    Risk,
+}
+
+impl IndicatorPage {
+    fn next(self) -> Self {
+        match self {
+            IndicatorPage::All => IndicatorPage::Basic,
+            IndicatorPage::Basic => IndicatorPage::Momentum,
+            IndicatorPage::Momentum => IndicatorPage::Volatility,
+            IndicatorPage::Volatility => IndicatorPage::Risk,
+            IndicatorPage::Risk => IndicatorPage::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IndicatorPage::All => "all",
+            IndicatorPage::Basic => "basic",
+            IndicatorPage::Momentum => "momentum",
+            IndicatorPage::Volatility => "volatility",
+            IndicatorPage::Risk => "risk",
+        }
+    }
+}
+
+/// Candle widths `{` / `}` step through, in ticks.
+const CANDLE_WINDOWS: [usize; 8] = [1, 2, 5, 10, 20, 30, 60, 120];
+
+/// A digit typed within this long of the previous one extends the stock
+/// number being jumped to instead of starting a new one.
+const JUMP_TIMEOUT: Duration = Duration::from_millis(800);
+/// How long a footer notice stays up.
+const NOTICE_TIME: Duration = Duration::from_secs(2);
+
+/// How often the correlation matrix is recomputed while shown; it costs
+/// O(N²) in the number of stocks, so not every frame.
+const CORRELATION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Slowest the adaptive frame rate drops to; the screen never freezes.
+const MIN_FPS: f64 = 2.0;
+/// Largest relative price move between two frames still counted as calm.
+const CALM_MOVE: f64 = 0.0005;
+/// Per-frame slow-down while calm.
+const FPS_DECAY: f64 = 0.9;
+
+/// Redraw rate that eases down toward `MIN_FPS` while prices barely move and
+/// jumps back to the `--max-fps` cap as soon as one moves noticeably.
+pub struct FramePacer {
+    max_fps: f64,
+    /// Current effective rate, shown in the footer.
+    pub fps: f64,
+    last_prices: Vec<f64>,
+}
+
+impl FramePacer {
+    pub fn new(max_fps: f64) -> Self {
+        let max_fps = max_fps.max(MIN_FPS);
+        Self {
+            max_fps,
+            fps: max_fps,
+            last_prices: vec![],
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps)
+    }
+
+    /// Adjusts the rate from how far the prices about to be drawn moved
+    /// since the previous frame.
+    pub fn observe(&mut self, prices: &[f64]) {
+        let moved = prices
+            .iter()
+            .zip(&self.last_prices)
+            .map(|(p, last)| (p - last).abs() / last.abs().max(f64::EPSILON))
+            .fold(0.0, f64::max);
+        self.fps = if moved > CALM_MOVE {
+            self.max_fps
+        } else {
+            (self.fps * FPS_DECAY).max(MIN_FPS)
+        };
+        self.last_prices = prices.to_vec();
+    }
+}
+
+/// A chart's y-axis range: fitted to the data each frame, or pinned with
+/// `y` / `--y-range`, in which case data outside it is clipped.
+#[derive(Default)]
+pub struct YAxis {
+    pub locked: Option<[f64; 2]>,
+    /// Range of the last draw, which is what `y` pins.
+    fitted: Cell<[f64; 2]>,
+}
+
+impl YAxis {
+    /// The range to draw with, given the one fitted to the data.
+    pub fn bounds(&self, fitted: (f64, f64)) -> (f64, f64) {
+        self.fitted.set([fitted.0, fitted.1]);
+        self.locked.map_or(fitted, |[lo, hi]| (lo, hi))
+    }
+
+    pub fn toggle_lock(&mut self) {
+        self.locked = match self.locked {
+            Some(_) => None,
+            None => Some(self.fitted.get()),
+        };
+    }
+}
+
+/// The legacy Windows console (anything not running under Windows Terminal)
+/// lacks glyphs for most non-ASCII symbols, and its width for them does not
+/// always agree with the one the layout assumes.
+fn narrow_console() -> bool {
+    cfg!(windows) && std::env::var_os("WT_SESSION").is_none()
+}
+
+/// Whether keys go to the normal bindings or answer a prompt.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    /// "Quit? (y/n)" is showing; the next key decides.
+    ConfirmingQuit,
+    /// The `:` command line is open; keys are typed into `App::command`.
+    Command,
+}
+
+/// Every stock's price at one moment, captured with `P`.
+pub struct Snapshot {
+    pub at: Instant,
+    pub prices: Vec<f64>,
+}
+
+/// Older and newer labelled snapshot being compared.
+pub type SnapshotPair<'a> = ((char, &'a Snapshot), (char, &'a Snapshot));
+
+/// An extra indicator computed from a stock's price history.
+pub type CustomIndicator = Box<dyn Fn(&[f64]) -> f64>;
+
+/// UI-only state driven by the keyboard; never touched by the updater threads.
+pub struct App {
+    pub view: View,
+    pub mode: Mode,
+    /// Ask before quitting on `q` (`--confirm-quit`).
+    pub confirm_quit: bool,
+    pub selected: usize,
+    pub multi_timeframe: bool,
+    pub fade: bool,
+    pub frontend_model: FrontendModel,
+    pub frontend_lag: usize,
+    /// Draw the selected stock's frontend average over the backend chart.
+    pub overlay_frontend: bool,
+    /// Plot per-tick returns instead of prices on the backend chart.
+    pub show_returns: bool,
+    /// Returns everything return-based is computed over (`N`).
+    pub return_kind: ReturnKind,
+    /// Ticks aggregated into each candle.
+    pub candle_window: usize,
+    /// Page of the candle grid showing, and how many the last draw had
+    /// (cells that fit depend on the terminal size).
+    pub candle_page: usize,
+    pub candle_pages: Cell<usize>,
+    /// Plot prices as log10 with the axis labelled in price.
+    pub log_scale: bool,
+    /// Number the backend x-axis by absolute tick instead of 0..plot_points.
+    pub absolute_x: bool,
+    /// Plot the backend against the last this-long of time
+    /// (`--history-seconds`) instead of by tick.
+    pub time_window: Option<Duration>,
+    /// Show the EWMA of tick latency instead of the latest sample.
+    pub smooth_latency: bool,
+    pub time_unit: TimeUnit,
+    pub indicator_page: IndicatorPage,
+    pub decimals: Decimals,
+    /// Ticks back from the newest the `←` / `→` crosshair is at, if shown.
+    pub crosshair: Option<usize>,
+    /// Show the raw tick under the crosshair in full (`I`).
+    pub inspect: bool,
+    /// Last crosshair, inspect or candle-page key while the charts are held
+    /// still for it; they follow the data again `inspect_hold` later.
+    inspecting: Option<Instant>,
+    inspect_hold: Duration,
+    /// Tick of the loaded replay the charts are drawn as of (Ctrl-← /
+    /// Ctrl-→), and how many ticks it has; 0 when there is nothing to scrub.
+    pub playback: Option<usize>,
+    pub playback_len: usize,
+    playback_step: usize,
+    /// Show the `?` key reference over everything else.
+    pub show_help: bool,
+    /// What has been typed on the `:` command line, while it is open.
+    pub command: String,
+    /// Characters the `T` ticker tape has scrolled by, while it is shown.
+    pub ticker: Option<usize>,
+    /// Stock number typed so far for a quick jump, and when its last digit
+    /// came.
+    jump: Option<(usize, Instant)>,
+    /// Short-lived footer message and when it was set.
+    notice: Option<(String, Instant)>,
+    /// Show the raw tick table (`t`).
+    pub show_ticks: bool,
+    /// Unchanged ticks after which a stock is shown as FROZEN.
+    pub frozen_ticks: u64,
+    /// Passed to `orders::risk_size` for the `size` column and `B` / `S`.
+    pub target_risk: f64,
+    /// Width in deviations of the VWAP bands on the selected stock's chart,
+    /// if they are drawn.
+    pub vwap_bands: Option<f64>,
+    /// Stocks whose spread is shown (`--pair`), as `a - beta·b`.
+    pub pair: Option<(usize, usize)>,
+    /// Draw the pointers panel as an aligned table (`--table`).
+    pub pointer_table: bool,
+    /// Captured with `P` under labels A, B, … (reused after Z).
+    pub snapshots: BTreeMap<char, Snapshot>,
+    /// Return correlations of every stock pair, while the panel is shown.
+    pub correlation: Option<Vec<Vec<f64>>>,
+    correlation_at: Option<Instant>,
+    pub zoom: Zoom,
+    /// Backend/frontend chart widths in percent when not zoomed.
+    pub layout: (u16, u16),
+    /// Tiled chart area once the user splits it; `None` is the classic view.
+    pub panes: Option<Pane>,
+    /// Pane receiving `Tab` / `c` / close, by leaf order.
+    pub focus: usize,
+    /// Y-axis padding as a fraction of the plotted range.
+    pub y_pad: f64,
+    pub backend_y: YAxis,
+    pub frontend_y: YAxis,
+    /// Ticks-per-column ratio (either way) the footer warns past; 0 is off.
+    pub density_warning: f64,
+    /// Most recent samples plotted; the stored history may be longer.
+    pub plot_points: usize,
+    /// |Δ| above which the frontend line starts to tint red.
+    pub diff_threshold: f64,
+    /// Ticks back the `%Δ` column compares against.
+    pub change_lookback: usize,
+    pub theme: Theme,
+    /// Draw with plain ASCII only (no braille markers or box borders).
+    pub ascii: bool,
+    /// Titles in ASCII only (`--plain-titles`, also implied by `ascii` and
+    /// picked up on Windows consoles), through `ui::title_for`.
+    pub plain_titles: bool,
+    /// Markers the charts plot with; `Ascii` whenever `ascii` is set.
+    pub resolution: Resolution,
+    /// Launch time, for the session clock in the footer.
+    pub start: Instant,
+    /// Zone of the footer's wall clock and exported timestamps (`--tz`).
+    pub zone: Zone,
+    pub pacer: FramePacer,
+    /// Frontend updates seen so far, set by the main loop before each draw.
+    pub frontend_version: u64,
+    /// When the charts held for inspection were copied, set by the main
+    /// loop; timed axes end there instead of at now, so they stay put.
+    pub held_at: Option<Instant>,
+    /// The overview's frontend chart and the version it was built from. The
+    /// frontend only refreshes a few times a second, so most frames reuse it.
+    pub frontend_chart: RefCell<Option<(u64, ChartSpec<'static>)>>,
+    /// Shown as extra columns after the built-ins, in registration order.
+    pub custom_indicators: Vec<(String, CustomIndicator)>,
+    n_stocks: usize,
+}
+
+impl App {
+    pub fn new(config: &Config, n_stocks: usize, start: Instant) -> Self {
+        let resolution = if config.ascii {
+            Resolution::Ascii
+        } else {
+            config.resolution.unwrap_or_else(Resolution::detect)
+        };
+        Self {
+            view: View::Overview,
+            mode: Mode::Normal,
+            confirm_quit: config.confirm_quit,
+            selected: 0,
+            multi_timeframe: false,
+            fade: true,
+            frontend_model: config.frontend_model,
+            frontend_lag: config.frontend_lag as usize,
+            overlay_frontend: false,
+            show_returns: false,
+            return_kind: ReturnKind::default(),
+            log_scale: config.log_scale,
+            candle_window: config.candle_window as usize,
+            candle_page: 0,
+            candle_pages: Cell::new(1),
+            absolute_x: false,
+            time_window: config.history_seconds.map(Duration::from_secs),
+            smooth_latency: false,
+            time_unit: TimeUnit::Auto,
+            indicator_page: IndicatorPage::All,
+            decimals: Decimals {
+                price: config.price_decimals,
+                avg: config.avg_decimals,
+                indicator: config.indicator_decimals,
+                percent: config.percent_decimals,
+            },
+            crosshair: None,
+            inspect: false,
+            inspecting: None,
+            inspect_hold: config.inspect_hold,
+            playback: None,
+            playback_len: 0,
+            playback_step: config.playback_step as usize,
+            show_help: false,
+            command: String::new(),
+            ticker: None,
+            jump: None,
+            notice: None,
+            show_ticks: false,
+            frozen_ticks: config.frozen_ticks,
+            density_warning: config.density_warning,
+            target_risk: config.target_risk,
+            vwap_bands: config.vwap_bands,
+            pair: config.pair,
+            pointer_table: config.table,
+            snapshots: BTreeMap::new(),
+            correlation: None,
+            correlation_at: None,
+            zoom: Zoom::Split,
+            layout: config.layout,
+            panes: None,
+            focus: 0,
+            y_pad: config.y_pad.max(0.0) / 100.0,
+            backend_y: YAxis {
+                locked: config.y_range.map(|(lo, hi)| [lo, hi]),
+                ..YAxis::default()
+            },
+            frontend_y: YAxis::default(),
+            plot_points: config.plot_points(),
+            diff_threshold: config.diff_threshold.max(f64::EPSILON),
+            change_lookback: config.change_lookback,
+            theme: Theme::new(config.background, config.heat_colors),
+            ascii: resolution == Resolution::Ascii,
+            plain_titles: resolution == Resolution::Ascii
+                || config.plain_titles
+                || narrow_console(),
+            resolution,
+            start,
+            zone: config.tz.unwrap_or_else(Zone::local),
+            pacer: FramePacer::new(config.max_fps),
+            frontend_version: 0,
+            held_at: None,
+            frontend_chart: RefCell::new(None),
+            custom_indicators: vec![],
+            n_stocks,
+        }
+    }
+
+    /// Adds a named indicator, evaluated on each stock's ticks every frame.
+    pub fn register_indicator(
+        &mut self,
+        name: impl Into<String>,
+        indicator: impl Fn(&[f64]) -> f64 + 'static,
+    ) {
+        self.custom_indicators
+            .push((name.into(), Box::new(indicator)));
+    }
+
+    /// After `a` / `d`: keeps the selection on an existing stock and makes
+    /// the correlation matrix resize on the next frame.
+    pub fn set_stock_count(&mut self, n: usize) {
+        self.n_stocks = n;
+        self.selected = self.selected.min(n - 1);
+        self.correlation_at = None;
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.n_stocks;
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = (self.selected + self.n_stocks - 1) % self.n_stocks;
+    }
+
+    /// Selects the stock whose number is being typed, digit by digit; a
+    /// number with no such stock is ignored with a footer notice.
+    pub fn jump_digit(&mut self, digit: usize) {
+        let now = Instant::now();
+        let number = match self.jump {
+            Some((typed, at)) if now.duration_since(at) < JUMP_TIMEOUT => {
+                typed.saturating_mul(10).saturating_add(digit)
+            }
+            _ => digit,
+        };
+        self.jump = Some((number, now));
+        if number < self.n_stocks {
+            self.selected = number;
+        } else {
+            self.notify(format!("no stock {} (0–{})", number, self.n_stocks - 1));
+        }
+    }
+
+    pub fn notify(&mut self, message: String) {
+        self.notice = Some((message, Instant::now()));
+    }
+
+    /// The footer notice, while it is still fresh.
+    pub fn notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < NOTICE_TIME)
+            .map(|(message, _)| message.as_str())
+    }
+
+    pub fn toggle_detail(&mut self) {
+        self.view = match self.view {
+            View::Detail => View::Overview,
+            _ => View::Detail,
+        };
+    }
+
+    pub fn toggle_candle_grid(&mut self) {
+        self.view = match self.view {
+            View::CandleGrid => View::Overview,
+            _ => View::CandleGrid,
+        };
+    }
+
+    pub fn next_candle_page(&mut self) {
+        let last = self.candle_pages.get().saturating_sub(1);
+        self.candle_page = (self.candle_page + 1).min(last);
+        self.hold_for_inspection();
+    }
+
+    pub fn prev_candle_page(&mut self) {
+        self.candle_page = self.candle_page.saturating_sub(1);
+        self.hold_for_inspection();
+    }
+
+    pub fn toggle_multi_timeframe(&mut self) {
+        self.multi_timeframe = !self.multi_timeframe;
+    }
+
+    pub fn toggle_fade(&mut self) {
+        self.fade = !self.fade;
+    }
+
+    pub fn toggle_overlay_frontend(&mut self) {
+        self.overlay_frontend = !self.overlay_frontend;
+    }
+
+    pub fn toggle_returns(&mut self) {
+        self.show_returns = !self.show_returns;
+    }
+
+    /// Switches between simple and log returns; the correlation matrix is
+    /// recomputed on the next frame.
+    pub fn toggle_log_returns(&mut self) {
+        self.return_kind = self.return_kind.toggled();
+        self.correlation_at = None;
+    }
+
+    /// Pins both overview charts at their current range, or lets both
+    /// auto-scale again if either is pinned.
+    pub fn toggle_y_lock(&mut self) {
+        if self.backend_y.locked.is_some() || self.frontend_y.locked.is_some() {
+            self.backend_y.locked = None;
+            self.frontend_y.locked = None;
+        } else {
+            self.backend_y.toggle_lock();
+            self.frontend_y.toggle_lock();
+        }
+    }
+
+    pub fn invalidate_frontend_chart(&mut self) {
+        *self.frontend_chart.get_mut() = None;
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+    }
+
+    pub fn toggle_ticker(&mut self) {
+        self.ticker = match self.ticker {
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+
+    /// Moves the tape one character left; called once per drawn frame.
+    pub fn advance_ticker(&mut self) {
+        if let Some(offset) = self.ticker.as_mut() {
+            *offset = offset.wrapping_add(1);
+        }
+    }
+
+    pub fn toggle_ticks(&mut self) {
+        self.show_ticks = !self.show_ticks;
+    }
+
+    /// Next preset candle width below the current one.
+    pub fn narrower_candles(&mut self) {
+        if let Some(&w) = CANDLE_WINDOWS
+            .iter()
+            .rev()
+            .find(|&&w| w < self.candle_window)
+        {
+            self.candle_window = w;
+        }
+    }
+
+    /// Next preset candle width above the current one.
+    pub fn wider_candles(&mut self) {
+        if let Some(&w) = CANDLE_WINDOWS.iter().find(|&&w| w > self.candle_window) {
+            self.candle_window = w;
+        }
+    }
+
+    pub fn toggle_log_scale(&mut self) {
+        self.log_scale = !self.log_scale;
+    }
+
+    pub fn toggle_absolute_x(&mut self) {
+        self.absolute_x = !self.absolute_x;
+    }
+
+    pub fn toggle_smooth_latency(&mut self) {
+        self.smooth_latency = !self.smooth_latency;
+    }
+
+    pub fn cycle_time_unit(&mut self) {
+        self.time_unit = self.time_unit.next();
+    }
+
+    pub fn cycle_indicator_page(&mut self) {
+        self.indicator_page = self.indicator_page.next();
+    }
+
+    /// Shows the crosshair at the newest tick, or moves it one tick back,
+    /// no further than the plotted window.
+    pub fn crosshair_left(&mut self) {
+        let oldest = self.plot_points - 1;
+        self.crosshair = Some(self.crosshair.map_or(0, |age| (age + 1).min(oldest)));
+        self.hold_for_inspection();
+    }
+
+    /// Moves the crosshair one tick forward; past the newest tick it hides,
+    /// which lets the charts go live again.
+    pub fn crosshair_right(&mut self) {
+        self.crosshair = self.crosshair.and_then(|age| age.checked_sub(1));
+        match self.crosshair {
+            Some(_) => self.hold_for_inspection(),
+            None if !self.inspect => self.resume_live(),
+            None => {}
+        }
+    }
+
+    /// Turning inspect on brings the crosshair up at the newest tick if it
+    /// was hidden, since that is what gets inspected.
+    pub fn toggle_inspect(&mut self) {
+        self.inspect = !self.inspect;
+        if self.inspect && self.crosshair.is_none() {
+            self.crosshair = Some(0);
+        }
+        self.hold_for_inspection();
+    }
+
+    /// Holds the charts where they are (or keeps holding them) so the data
+    /// streaming in does not scroll away what is being looked at.
+    pub fn hold_for_inspection(&mut self) {
+        if !self.inspect_hold.is_zero() {
+            self.inspecting = Some(Instant::now());
+        }
+    }
+
+    pub fn open_command(&mut self) {
+        self.mode = Mode::Command;
+        self.command.clear();
+    }
+
+    /// Closes the command line, returning what was typed on it.
+    pub fn close_command(&mut self) -> String {
+        self.mode = Mode::Normal;
+        std::mem::take(&mut self.command)
+    }
+
+    pub fn resume_live(&mut self) {
+        self.inspecting = None;
+        self.playback = None;
+    }
+
+    /// Moves the playback cursor back a step, putting it at `played` (how
+    /// far the replay has got) first if it was not showing.
+    pub fn playback_back(&mut self, played: usize) {
+        if self.playback_len == 0 {
+            self.notify("nothing to scrub: needs --replay or --replay-ohlc".to_string());
+            return;
+        }
+        let at = self.playback.unwrap_or(played.min(self.playback_len));
+        self.playback = Some(at.saturating_sub(self.playback_step));
+    }
+
+    /// Moves the playback cursor forward a step; past the end of the replay
+    /// it hides and the charts are live again.
+    pub fn playback_forward(&mut self) {
+        self.playback = self
+            .playback
+            .map(|at| at + self.playback_step)
+            .filter(|&at| at <= self.playback_len);
+    }
+
+    /// Whether the charts are being held still; the simulation carries on
+    /// underneath either way.
+    pub fn inspecting(&self) -> bool {
+        self.inspecting
+            .is_some_and(|at| at.elapsed() < self.inspect_hold)
+    }
+
+    /// Stores the current prices under the next free label, or over the
+    /// oldest snapshot once all 26 are taken.
+    pub fn take_snapshot(&mut self, prices: Vec<f64>) {
+        let label = ('A'..='Z')
+            .find(|l| !self.snapshots.contains_key(l))
+            .or_else(|| {
+                self.snapshots
+                    .iter()
+                    .min_by_key(|(_, s)| s.at)
+                    .map(|(l, _)| *l)
+            })
+            .unwrap_or('A');
+        self.snapshots.insert(
+            label,
+            Snapshot {
+                at: Instant::now(),
+                prices,
+            },
+        );
+    }
+
+    /// The two most recent snapshots, older first, once there are two.
+    pub fn snapshot_pair(&self) -> Option<SnapshotPair<'_>> {
+        let mut by_time: Vec<_> = self.snapshots.iter().map(|(l, s)| (*l, s)).collect();
+        by_time.sort_by_key(|(_, s)| s.at);
+        let newer = by_time.pop()?;
+        let older = by_time.pop()?;
+        Some((older, newer))
+    }
+
+    pub fn toggle_correlation(&mut self) {
+        self.correlation = match self.correlation {
+            Some(_) => None,
+            None => Some(vec![]),
+        };
+        self.correlation_at = None;
+    }
+
+    /// Recomputes the correlation matrix from each stock's history, at most
+    /// every `CORRELATION_INTERVAL` and only while it is shown.
+    pub fn refresh_correlation(&mut self, histories: &[&[f64]]) {
+        let Some(matrix) = self.correlation.as_mut() else {
+            return;
+        };
+        if self
+            .correlation_at
+            .is_some_and(|at| at.elapsed() < CORRELATION_INTERVAL)
+        {
+            return;
+        }
+        let returns: Vec<Vec<f64>> = histories.iter().map(|h| self.return_kind.of(h)).collect();
+        *matrix = indicators::correlation_matrix(&returns);
+        self.correlation_at = Some(Instant::now());
+    }
+
+    /// Splits the focused pane (or, the first time, turns the classic view
+    /// into a backend and a frontend pane).
+    pub fn split_pane(&mut self, direction: Direction) {
+        match &mut self.panes {
+            Some(panes) => panes.split(self.focus, direction),
+            None => {
+                self.panes = Some(Pane::first_split(direction));
+                self.focus = 0;
+            }
+        }
+    }
+
+    /// Closes the focused pane; closing the last one restores the classic view.
+    pub fn close_pane(&mut self) {
+        self.panes = self.panes.take().and_then(|p| p.close(self.focus));
+        let leaves = self.panes.as_ref().map_or(1, Pane::leaves);
+        self.focus = self.focus.min(leaves - 1);
+    }
+
+    pub fn focus_next_pane(&mut self) {
+        if let Some(panes) = &self.panes {
+            self.focus = (self.focus + 1) % panes.leaves();
+        }
+    }
+
+    pub fn cycle_pane_content(&mut self) {
+        if let Some(panes) = &mut self.panes {
+            panes.cycle_content(self.focus);
+        }
+    }
+
+    pub fn cycle_zoom(&mut self) {
+        self.zoom = match self.zoom {
+            Zoom::Split => Zoom::Backend,
+            Zoom::Backend => Zoom::Frontend,
+            Zoom::Frontend => Zoom::Split,
+        };
+    }
+}
@@ -0,0 +1,468 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    symbols,
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Widget},
+    Frame,
+};
+
+use crate::app::App;
+use crate::ui::panel;
+
+/// How finely charts plot, from sharpest to most widely supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Resolution {
+    /// Braille dots, 2×4 per cell; needs a font with the braille block.
+    Braille,
+    /// Upper / lower half blocks, 1×2 per cell.
+    HalfBlock,
+    /// Whole cells.
+    Block,
+    /// Plain ASCII characters, borders included (same as --ascii).
+    Ascii,
+}
+
+impl Resolution {
+    /// Braille, except on terminals known to lack the glyphs (the Linux
+    /// console), which get half blocks.
+    pub fn detect() -> Self {
+        match std::env::var("TERM") {
+            Ok(term) if term == "linux" || term.starts_with("vt") => Resolution::HalfBlock,
+            _ => Resolution::Braille,
+        }
+    }
+
+    /// The marker a series asking for the sharpest one is drawn with. Dot
+    /// and block series are already one per cell and keep theirs.
+    fn marker(self, wanted: symbols::Marker) -> symbols::Marker {
+        match (self, wanted) {
+            (Resolution::HalfBlock, symbols::Marker::Braille) => symbols::Marker::HalfBlock,
+            (Resolution::Block, symbols::Marker::Braille) => symbols::Marker::Block,
+            _ => wanted,
+        }
+    }
+}
+
+/// One line or scatter on a chart, kept independent of how it is drawn so the
+/// same charts can go through ratatui's `Chart` or the ASCII rasterizer.
+pub struct Series<'a> {
+    pub name: Option<String>,
+    pub points: Cow<'a, [(f64, f64)]>,
+    pub style: Style,
+    pub marker: symbols::Marker,
+    pub graph_type: GraphType,
+    /// Drawn in place of the marker at each point (e.g. trade arrows).
+    pub glyph: Option<&'static str>,
+}
+
+impl<'a> Series<'a> {
+    pub fn new(points: impl Into<Cow<'a, [(f64, f64)]>>, marker: symbols::Marker) -> Self {
+        Self {
+            name: None,
+            points: points.into(),
+            style: Style::default(),
+            marker,
+            graph_type: GraphType::Scatter,
+            glyph: None,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn line(mut self) -> Self {
+        self.graph_type = GraphType::Line;
+        self
+    }
+
+    pub fn glyph(mut self, glyph: &'static str) -> Self {
+        self.glyph = Some(glyph);
+        self
+    }
+}
+
+pub struct ChartSpec<'a> {
+    pub title: String,
+    pub series: Vec<Series<'a>>,
+    pub x_bounds: [f64; 2],
+    pub y_bounds: [f64; 2],
+    /// Tick numbers under the x-axis; empty for the relative axis. Not drawn
+    /// by the ASCII rasterizer.
+    pub x_labels: Vec<String>,
+    /// Values beside the y-axis; only set on a log axis. Not drawn by the
+    /// ASCII rasterizer either.
+    pub y_labels: Vec<String>,
+}
+
+impl<'a> ChartSpec<'a> {
+    pub fn new(title: String, x_len: usize, (min_y, max_y): (f64, f64)) -> Self {
+        Self {
+            title,
+            series: vec![],
+            x_bounds: [0.0, x_len as f64],
+            y_bounds: [min_y, max_y],
+            x_labels: vec![],
+            y_labels: vec![],
+        }
+    }
+
+    /// Switches the x-axis to absolute tick numbers, keeping its width but
+    /// ending at `last_tick`, and labels both ends and the middle. Call before
+    /// `with_zero_line` so the reference line spans the new range.
+    pub fn ending_at(mut self, last_tick: u64) -> Self {
+        let end = last_tick as f64;
+        let width = self.x_bounds[1] - self.x_bounds[0];
+        self.x_bounds = [end - width, end];
+        self.x_labels = [end - width, end - width / 2.0, end]
+            .iter()
+            .map(|x| format!("{:.0}", x))
+            .collect();
+        self
+    }
+
+    /// Switches the x-axis to seconds before now over the last `window`,
+    /// labelled at both ends and the middle. Call before `with_zero_line`.
+    pub fn over_last(mut self, window: Duration) -> Self {
+        let secs = window.as_secs_f64();
+        self.x_bounds = [-secs, 0.0];
+        self.x_labels = vec![
+            format!("-{:.0}s", secs),
+            format!("-{:.0}s", secs / 2.0),
+            "now".to_string(),
+        ];
+        self
+    }
+
+    /// Replots every series as `log10(y)`, dropping non-positive points,
+    /// and labels the axis with prices to `precision` decimals. A bound at
+    /// or below zero falls back to the smallest positive point; with no
+    /// positive range at all the chart stays linear. Call last, once every
+    /// series is pushed.
+    pub fn log_y(mut self, precision: usize) -> Self {
+        let lowest_point = self
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter().map(|(_, y)| *y))
+            .filter(|y| *y > 0.0)
+            .fold(f64::INFINITY, f64::min);
+        let [min_y, max_y] = self
+            .y_bounds
+            .map(|y| if y > 0.0 { y } else { lowest_point }.log10());
+        if !max_y.is_finite() || min_y >= max_y {
+            return self;
+        }
+        for s in &mut self.series {
+            s.points = s
+                .points
+                .iter()
+                .filter(|(_, y)| *y > 0.0)
+                .map(|&(x, y)| (x, y.log10()))
+                .collect::<Vec<_>>()
+                .into();
+        }
+        self.y_bounds = [min_y, max_y];
+        self.y_labels = [min_y, (min_y + max_y) / 2.0, max_y]
+            .iter()
+            .map(|y| format!("{:.*}", precision, 10f64.powf(*y)))
+            .collect();
+        self
+    }
+
+    /// Adds a horizontal reference line at y = 0 when the y-axis range
+    /// crosses zero. Pushed first so the data draws over it.
+    pub fn with_zero_line(mut self, color: Color) -> Self {
+        let [min_y, max_y] = self.y_bounds;
+        if min_y < 0.0 && max_y > 0.0 {
+            let pts = vec![(self.x_bounds[0], 0.0), (self.x_bounds[1], 0.0)];
+            let zero = Series::new(pts, symbols::Marker::Braille)
+                .style(Style::default().fg(color))
+                .line();
+            self.series.insert(0, zero);
+        }
+        self
+    }
+
+    /// Adds a horizontal line at `y` across the chart, if it is in range.
+    pub fn push_level(&mut self, y: f64, style: Style) {
+        let [min_y, max_y] = self.y_bounds;
+        if (min_y..=max_y).contains(&y) {
+            let pts = vec![(self.x_bounds[0], y), (self.x_bounds[1], y)];
+            self.series.push(
+                Series::new(pts, symbols::Marker::Braille)
+                    .style(style)
+                    .line(),
+            );
+        }
+    }
+
+    pub fn push(&mut self, series: impl IntoIterator<Item = Series<'a>>) {
+        self.series.extend(series);
+    }
+
+    pub fn render(&self, f: &mut Frame, app: &App, area: Rect) {
+        let block = panel(self.title.clone(), app);
+        if app.ascii {
+            f.render_widget(AsciiChart { block, spec: self }, area);
+            return;
+        }
+
+        // ratatui only draws its own markers (and keeps only the color of
+        // canvas cells), so glyph series go out as dots in a placeholder
+        // color and are swapped for the glyph in their real style below
+        let placeholder = |i: usize| Color::Rgb(0, 1, i as u8);
+        let datasets = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let (marker, style) = match s.glyph {
+                    Some(_) => (symbols::Marker::Dot, Style::default().fg(placeholder(i))),
+                    None => (app.resolution.marker(s.marker), s.style),
+                };
+                let ds = Dataset::default()
+                    .marker(marker)
+                    .graph_type(s.graph_type)
+                    .style(style)
+                    .data(&s.points);
+                match &s.name {
+                    Some(name) => ds.name(name.clone()),
+                    None => ds,
+                }
+            })
+            .collect();
+        let chart = Chart::new(datasets)
+            .block(block)
+            .x_axis(
+                Axis::default()
+                    .bounds(self.x_bounds)
+                    .labels(self.x_labels.clone()),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds(self.y_bounds)
+                    .labels(self.y_labels.clone()),
+            );
+        f.render_widget(chart, area);
+
+        let buf = f.buffer_mut();
+        for (i, s) in self.series.iter().enumerate() {
+            let Some(glyph) = s.glyph else { continue };
+            for pos in area.positions() {
+                if let Some(cell) = buf.cell_mut(pos) {
+                    if cell.fg == placeholder(i) && cell.symbol() == symbols::DOT {
+                        cell.set_symbol(glyph).set_style(s.style);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Splits a series so its oldest quarter (next in line for eviction) is drawn
+/// dimmed. The faded part stays unnamed to keep the legend to one entry per
+/// series, and shares its last point with the recent part so lines connect.
+pub fn faded_series<'a>(series: Series<'a>, color: Color, fade: bool) -> Vec<Series<'a>> {
+    let style = Style::default().fg(color);
+    let n_faded = series.points.len() / 4;
+    if !fade || n_faded == 0 {
+        return vec![series.style(style)];
+    }
+
+    let faded = Series {
+        name: None,
+        points: series.points[..=n_faded].to_vec().into(),
+        style: style.add_modifier(Modifier::DIM),
+        marker: series.marker,
+        graph_type: series.graph_type,
+        glyph: series.glyph,
+    };
+    let recent = Series {
+        points: series.points[n_faded..].to_vec().into(),
+        ..series
+    };
+    vec![faded, recent.style(style)]
+}
+
+/// The last `n` samples (or all of them when there are fewer).
+pub fn tail(values: &[f64], n: usize) -> &[f64] {
+    &values[values.len().saturating_sub(n)..]
+}
+
+/// Points at seconds before `now`, pairing values with timestamps from the
+/// newest backwards so a shorter series (e.g. returns) still lines up.
+pub fn to_points_timed(values: &[f64], times: &[Instant], now: Instant) -> Vec<(f64, f64)> {
+    let mut points: Vec<(f64, f64)> = values
+        .iter()
+        .rev()
+        .zip(times.iter().rev())
+        .map(|(v, t)| (-now.saturating_duration_since(*t).as_secs_f64(), *v))
+        .collect();
+    points.reverse();
+    points
+}
+
+/// Like `to_points`, but numbered so the newest sample lands on `last_tick`.
+pub fn to_points_ending_at(history: &[f64], last_tick: u64) -> Vec<(f64, f64)> {
+    let first = last_tick as f64 + 1.0 - history.len() as f64;
+    history
+        .iter()
+        .enumerate()
+        .map(|(i, y)| (first + i as f64, *y))
+        .collect()
+}
+
+pub fn to_points(history: &[f64]) -> Vec<(f64, f64)> {
+    history
+        .iter()
+        .enumerate()
+        .map(|(i, y)| (i as f64, *y))
+        .collect()
+}
+
+/// Smallest padding applied on each side, so a flat series still gets a
+/// visible band instead of a zero-height axis.
+const MIN_Y_PAD: f64 = 0.01;
+
+/// Y-axis range of the given values, padded on each side by `pad_frac` of
+/// the range (at least `MIN_Y_PAD`). An empty series maps to `[0, 1]`.
+pub fn y_bounds<'a>(values: impl IntoIterator<Item = &'a f64>, pad_frac: f64) -> (f64, f64) {
+    let (min, max) = values
+        .into_iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    if min > max {
+        return (0.0, 1.0);
+    }
+    let pad = ((max - min) * pad_frac).max(MIN_Y_PAD);
+    (min - pad, max + pad)
+}
+
+// -------------------- ASCII rasterizer --------------------
+
+/// Plain-ASCII stand-in for `Chart` on terminals without braille/Unicode:
+/// `*` marks samples, `.` fills horizontal steps and `|` vertical ones.
+struct AsciiChart<'a> {
+    block: Block<'a>,
+    spec: &'a ChartSpec<'a>,
+}
+
+impl Widget for AsciiChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let inner = self.block.inner(area);
+        self.block.render(area, buf);
+        if inner.width < 2 || inner.height < 2 {
+            return;
+        }
+
+        let [x0, x1] = self.spec.x_bounds;
+        let [y0, y1] = self.spec.y_bounds;
+        let (w, h) = ((inner.width - 1) as f64, (inner.height - 1) as f64);
+        let to_cell = |(x, y): (f64, f64)| -> Option<(i32, i32)> {
+            if !(x0..=x1).contains(&x) || !(y0..=y1).contains(&y) {
+                return None;
+            }
+            let col = ((x - x0) / (x1 - x0) * w).round() as i32;
+            let row = ((y1 - y) / (y1 - y0) * h).round() as i32;
+            Some((col, row))
+        };
+        let mut put = |(col, row): (i32, i32), ch: char, style: Style| {
+            let pos = (inner.x + col as u16, inner.y + row as u16);
+            if let Some(cell) = buf.cell_mut(pos) {
+                cell.set_char(ch).set_style(style);
+            }
+        };
+
+        for s in &self.spec.series {
+            let mut prev = None;
+            for &point in s.points.iter() {
+                let Some(cell) = to_cell(point) else {
+                    prev = None;
+                    continue;
+                };
+                if let (Some(from), GraphType::Line) = (prev, s.graph_type) {
+                    for (step, ch) in connect(from, cell) {
+                        put(step, ch, s.style);
+                    }
+                }
+                let ch = s.glyph.and_then(|g| g.chars().next()).unwrap_or('*');
+                put(cell, ch, s.style);
+                prev = Some(cell);
+            }
+        }
+    }
+}
+
+/// Cells strictly between two samples: `.` at the interpolated row of each
+/// intermediate column, then `|` for whatever rise remains in the last
+/// column.
+fn connect((c0, r0): (i32, i32), (c1, r1): (i32, i32)) -> Vec<((i32, i32), char)> {
+    let mut cells = vec![];
+    let dc = c1 - c0;
+    let mut last_row = r0;
+    for c in (c0 + 1)..c1 {
+        let row = r0 + ((r1 - r0) as f64 * (c - c0) as f64 / dc as f64).round() as i32;
+        cells.push(((c, row), '.'));
+        last_row = row;
+    }
+    let (lo, hi) = (last_row.min(r1), last_row.max(r1));
+    for r in (lo + 1)..hi {
+        cells.push(((c1, r), '|'));
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_ordered((min, max): (f64, f64)) {
+        assert!(min.is_finite() && max.is_finite());
+        assert!(min < max, "{} !< {}", min, max);
+    }
+
+    #[test]
+    fn y_bounds_pads_normal_range() {
+        let (min, max) = y_bounds(&[1.0, 3.0, 2.0], 0.1);
+        assert!((min - 0.8).abs() < 1e-12);
+        assert!((max - 3.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn y_bounds_gives_flat_series_a_band() {
+        let bounds = y_bounds(&[5.0, 5.0, 5.0], 0.1);
+        assert_ordered(bounds);
+        assert_eq!(bounds, (5.0 - MIN_Y_PAD, 5.0 + MIN_Y_PAD));
+    }
+
+    #[test]
+    fn y_bounds_single_element() {
+        let bounds = y_bounds(&[42.0], 0.05);
+        assert_ordered(bounds);
+        assert!(bounds.0 < 42.0 && bounds.1 > 42.0);
+    }
+
+    #[test]
+    fn y_bounds_contains_large_outlier() {
+        let bounds = y_bounds(&[1.0, 2.0, 1e12, 1.5], 0.05);
+        assert_ordered(bounds);
+        assert!(bounds.0 < 1.0 && bounds.1 > 1e12);
+    }
+
+    #[test]
+    fn y_bounds_of_empty_series() {
+        assert_eq!(y_bounds(&[], 0.05), (0.0, 1.0));
+    }
+}
@@ -0,0 +1,164 @@
+//! Periodic state checkpoints (`--checkpoint`) and resuming from one
+//! (`--load-state`), so a crash late in a long run loses at most one
+//! interval.
+//!
+//! A checkpoint is a JSON array of `StockState`. Each write first shifts
+//! the older files along (`<path>` → `<path>.1` → … → `<path>.<KEEP>`), so
+//! a file corrupted mid-write still leaves the previous ones. `<path>`
+//! itself is only ever replaced by a rename, so it never goes missing.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::sim::TickGrid;
+use crate::MarketData;
+
+/// Older checkpoints kept next to the newest one.
+const KEEP: usize = 3;
+
+#[derive(Serialize, Deserialize)]
+pub struct StockState {
+    symbol: String,
+    price: f64,
+    ticks: u64,
+    /// Oldest first, seed padding included.
+    history: Vec<f64>,
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Writes every stock's state to `path`, rotating earlier checkpoints. The
+/// new file is written aside and renamed into place, so `path` is always
+/// complete.
+pub fn save(path: &Path, md_vec: &[MarketData]) -> io::Result<()> {
+    let states: Vec<StockState> = md_vec
+        .iter()
+        .map(|md| StockState {
+            symbol: md.symbol.clone(),
+            price: *md.price.read().unwrap(),
+            ticks: md.ticks,
+            history: md.history.clone(),
+        })
+        .collect();
+    let partial = rotated(path, 0);
+    let mut out = BufWriter::new(File::create(&partial)?);
+    serde_json::to_writer(&mut out, &states)?;
+    writeln!(out)?;
+    out.flush()?;
+    drop(out);
+
+    for n in (1..KEEP).rev() {
+        let older = rotated(path, n);
+        if older.exists() {
+            fs::rename(&older, rotated(path, n + 1))?;
+        }
+    }
+    if path.exists() {
+        // Linked (or copied) rather than moved, so `path` stays in place
+        // until the rename below swaps the new file over it
+        let previous = rotated(path, 1);
+        if fs::hard_link(path, &previous).is_err() {
+            fs::copy(path, &previous)?;
+        }
+    }
+    fs::rename(&partial, path)
+}
+
+pub fn load(path: &Path) -> io::Result<Vec<StockState>> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/// Puts saved states back on the stocks with the same symbol, returning how
+/// many matched. The history keeps its newest `history_len` samples, padded
+/// with its oldest one if shorter; sample times restart at now.
+pub fn restore(md_vec: &mut [MarketData], states: Vec<StockState>, history_len: usize) -> usize {
+    let mut restored = 0;
+    for state in states {
+        let Some(md) = md_vec.iter_mut().find(|md| md.symbol == state.symbol) else {
+            continue;
+        };
+        let mut history = state.history;
+        history.drain(..history.len().saturating_sub(history_len));
+        let pad = history_len - history.len();
+        let first = history.first().copied().unwrap_or(state.price);
+        history.splice(0..0, std::iter::repeat_n(first, pad));
+
+        *md.price.write().unwrap() = state.price;
+        md.grid = md.grid.map(|g| TickGrid::new(g.size(), state.price));
+        md.ticks = state.ticks;
+        md.times = vec![Instant::now(); history.len()];
+        md.volumes = vec![0; history.len()];
+        md.history = history;
+        restored += 1;
+    }
+    restored
+}
+
+/// Starts a thread saving a checkpoint every `interval`. Only the copy of
+/// the stocks is made under the market lock; serializing happens outside.
+pub fn spawn_writer(path: PathBuf, interval: Duration, market_data: Arc<RwLock<Vec<MarketData>>>) {
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        let md_vec = market_data.read().unwrap().clone();
+        match save(&path, &md_vec) {
+            Ok(()) => info!("Checkpointed {} stocks to {}", md_vec.len(), path.display()),
+            Err(e) => error!("Checkpoint to {} failed: {:?}", path.display(), e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    use crate::config::Config;
+    use crate::instruments;
+
+    fn stocks() -> Vec<MarketData> {
+        let config = Config::parse_from(["rust_hft_tui", "--history", "4"]);
+        instruments::defaults()
+            .iter()
+            .enumerate()
+            .map(|(i, inst)| MarketData::new(i, inst, &config))
+            .collect()
+    }
+
+    #[test]
+    fn saved_state_restores_onto_fresh_stocks_and_rotates_the_old_file() {
+        let dir = std::env::temp_dir().join(format!("hft-checkpoint-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+
+        let mut md_vec = stocks();
+        save(&path, &md_vec).unwrap();
+        *md_vec[1].price.write().unwrap() = 123.5;
+        md_vec[1].ticks = 7;
+        md_vec[1].history = vec![120.0, 121.0, 122.0, 123.5];
+        save(&path, &md_vec).unwrap();
+        assert!(rotated(&path, 1).exists());
+        assert!(!rotated(&path, 0).exists());
+
+        let mut fresh = stocks();
+        let restored = restore(&mut fresh, load(&path).unwrap(), 4);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(restored, fresh.len());
+        assert_eq!(*fresh[1].price.read().unwrap(), 123.5);
+        assert_eq!(fresh[1].ticks, 7);
+        assert_eq!(fresh[1].history, vec![120.0, 121.0, 122.0, 123.5]);
+        assert_eq!(fresh[1].times.len(), 4);
+    }
+}
@@ -0,0 +1,97 @@
+//! Wall-clock time in a chosen zone (`--tz`), for the footer clock and the
+//! timestamps in exports. Named zones come from `chrono-tz`'s bundled tz
+//! database, so they work without a system zoneinfo tree.
+
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, TimeZone};
+use chrono_tz::Tz;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Zone {
+    /// Wherever the process runs, as `chrono::Local` finds it.
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    pub fn local() -> Self {
+        Zone::Local
+    }
+
+    /// A zone by its tz database name (`Europe/London`, `UTC`, …).
+    pub fn named(name: &str) -> Result<Self, String> {
+        Tz::from_str(name)
+            .map(Zone::Named)
+            .map_err(|_| format!("{}: no such time zone", name))
+    }
+
+    /// `2024-03-31T03:00:00.250+02:00`, to the millisecond.
+    pub fn iso8601(&self, at: SystemTime) -> String {
+        self.format(at, "%Y-%m-%dT%H:%M:%S%.3f%:z")
+    }
+
+    /// `14:03:22 CEST` for the footer. Local time has no abbreviation to
+    /// show, so it gets its offset instead.
+    pub fn clock(&self, at: SystemTime) -> String {
+        self.format(at, "%H:%M:%S %Z")
+    }
+
+    fn format(&self, at: SystemTime, fmt: &str) -> String {
+        match self {
+            Zone::Local => in_zone(&Local, at).format(fmt).to_string(),
+            Zone::Named(tz) => in_zone(tz, at).format(fmt).to_string(),
+        }
+    }
+}
+
+fn in_zone<Z: TimeZone>(zone: &Z, at: SystemTime) -> DateTime<Z> {
+    DateTime::<chrono::Utc>::from(at).with_timezone(zone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn at(unix: i64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(unix as u64)
+    }
+
+    #[test]
+    fn utc_formats_with_a_zero_offset() {
+        let zone = Zone::named("UTC").unwrap();
+        let t = at(1_711_846_800) + Duration::from_millis(250);
+        assert_eq!(zone.iso8601(t), "2024-03-31T01:00:00.250+00:00");
+        assert_eq!(zone.clock(t), "01:00:00 UTC");
+    }
+
+    #[test]
+    fn named_zones_switch_to_daylight_time() {
+        let berlin = Zone::named("Europe/Berlin").unwrap();
+        // 2024-03-31 01:00 UTC is 02:00 CET, the moment clocks go to 03:00
+        assert_eq!(
+            berlin.iso8601(at(1_711_846_799)),
+            "2024-03-31T01:59:59.000+01:00"
+        );
+        assert_eq!(
+            berlin.iso8601(at(1_711_846_800)),
+            "2024-03-31T03:00:00.000+02:00"
+        );
+        assert_eq!(berlin.clock(at(1_729_990_800)), "02:00:00 CET");
+    }
+
+    #[test]
+    fn southern_zones_span_the_new_year() {
+        let sydney = Zone::named("Australia/Sydney").unwrap();
+        assert_eq!(sydney.clock(at(1_704_067_200)), "11:00:00 AEDT");
+        assert_eq!(sydney.clock(at(1_719_792_000)), "10:00:00 AEST");
+    }
+
+    #[test]
+    fn bad_names_are_refused() {
+        assert!(Zone::named("../etc/passwd").is_err());
+        assert!(Zone::named("Not/AZone").is_err());
+    }
+}
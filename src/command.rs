@@ -0,0 +1,33 @@
+//! The `:` command line, for settings a single key cannot carry because
+//! they take a value.
+
+/// One entered command line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    /// `alert [at] PRICE`: tag the selected stock whenever it crosses PRICE.
+    Alert(f64),
+    /// `unalert PRICE`: drop one of the selected stock's levels.
+    Unalert(f64),
+    /// `alert clear`: drop all of them.
+    ClearAlerts,
+}
+
+pub fn parse(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let price = |word: &str| -> Result<f64, String> {
+        match word.parse::<f64>() {
+            Ok(p) if p.is_finite() => Ok(p),
+            _ => Err(format!("{}: not a price", word)),
+        }
+    };
+    match words.as_slice() {
+        ["alert", "clear"] => Ok(Command::ClearAlerts),
+        ["alert", "at", p] | ["alert", p] => price(p).map(Command::Alert),
+        ["unalert", p] => price(p).map(Command::Unalert),
+        [] => Err("no command".to_string()),
+        _ => Err(format!(
+            "{}: expected alert PRICE, unalert PRICE or alert clear",
+            line.trim()
+        )),
+    }
+}
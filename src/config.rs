@@ -0,0 +1,586 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+
+use crate::chart::Resolution;
+use crate::clock::Zone;
+use crate::frontend::FrontendModel;
+use crate::sim::{Band, Jumps, Scenario, ZeroPolicy};
+use crate::theme::{Background, Rgb};
+use crate::ticklog::{parse_flush_policy, FlushPolicy};
+
+/// Samples kept per stock when `--history` is not given.
+pub const DEFAULT_HISTORY_LEN: usize = 50;
+
+/// Tick interval used for stocks without an entry in `--stock-rates`.
+pub const DEFAULT_TICK_MS: u64 = 100;
+
+/// Command line options for the simulator.
+#[derive(Parser, Debug, Clone)]
+#[command(
+    version,
+    about = "Terminal market data simulator backed by Postgres and Redis"
+)]
+pub struct Config {
+    /// Stock ids whose price may go below zero (e.g. spreads), comma separated
+    #[arg(long, value_delimiter = ',', value_name = "IDS")]
+    pub allow_negative: Vec<usize>,
+
+    /// Per-stock price ranges as ID:LOW-HIGH, comma separated (e.g.
+    /// 0:90-110); random-walk moves past an edge bounce back inside
+    #[arg(
+        long,
+        value_name = "ID:LOW-HIGH",
+        value_delimiter = ',',
+        value_parser = parse_price_band
+    )]
+    pub price_bands: Vec<(usize, Band)>,
+
+    /// Start with the backend chart's y-axis pinned to LOW-HIGH (`y` unpins)
+    #[arg(long, value_name = "LOW-HIGH", value_parser = parse_range)]
+    pub y_range: Option<(f64, f64)>,
+
+    /// Per-stock tick interval in milliseconds, comma separated (e.g. 10,50,100)
+    #[arg(long, value_delimiter = ',', value_name = "MS")]
+    pub stock_rates: Vec<u64>,
+
+    /// Unix socket path serving a JSON snapshot of prices and indicators
+    /// (Unix only)
+    #[arg(long, value_name = "PATH", value_parser = parse_socket_path)]
+    pub ipc_socket: Option<PathBuf>,
+
+    /// Play back a recorded `stock_id,price` CSV instead of the random walk
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Play back `time,open,high,low,close` bars, one per tick, instead of
+    /// the random walk; repeat for more stocks (the n-th file is stock n)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["replay", "stdin"])]
+    pub replay_ohlc: Vec<PathBuf>,
+
+    /// Ticks the Ctrl-← / Ctrl-→ playback cursor moves through a loaded
+    /// replay per press
+    #[arg(
+        long,
+        value_name = "TICKS",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub playback_step: u64,
+
+    /// Read `SYMBOL PRICE` or `{"symbol": .., "price": ..}` lines from stdin
+    /// instead of the random walk
+    #[arg(long, conflicts_with = "replay")]
+    pub stdin: bool,
+
+    /// JSON array of `{symbol, name, initial_price, sigma}` objects to simulate
+    #[arg(long, value_name = "FILE")]
+    pub instruments: Option<PathBuf>,
+
+    /// Chart y-axis padding as a percentage of the plotted price range
+    #[arg(long, value_name = "PERCENT", default_value_t = 5.0)]
+    pub y_pad: f64,
+
+    /// Samples kept per stock (indicators use the whole buffer)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_HISTORY_LEN,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..)
+    )]
+    pub history: usize,
+
+    /// Keep each stock's last SECS of samples instead of the last --history
+    /// ones, and plot the backend against time
+    #[arg(
+        long,
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub history_seconds: Option<u64>,
+
+    /// Most recent samples plotted per chart [default: --history]
+    #[arg(long, value_name = "N")]
+    pub plot_points: Option<usize>,
+
+    /// Terminal background, used to pick readable colors
+    #[arg(long, value_enum, default_value_t = Background::Dark)]
+    pub background: Background,
+
+    /// Ends of the heat gradient (correlation grid) as hex RGB for -1 and
+    /// +1, e.g. c00000,00a000
+    #[arg(long, value_name = "LOW,HIGH", value_parser = parse_heat_colors)]
+    pub heat_colors: Option<(Rgb, Rgb)>,
+
+    /// Time zone of the footer clock and export timestamps, by tz database
+    /// name (e.g. Europe/London); default local time
+    #[arg(long, value_name = "ZONE", value_parser = Zone::named)]
+    pub tz: Option<Zone>,
+
+    /// What a random-walk stock does when its price hits zero
+    #[arg(long, value_enum, default_value_t = ZeroPolicy::Clamp)]
+    pub zero_policy: ZeroPolicy,
+
+    /// How the frontend value trails the market price
+    #[arg(long, value_enum, default_value_t = FrontendModel::Avg)]
+    pub frontend_model: FrontendModel,
+
+    /// Ticks averaged or delayed by, or refreshes smoothed or held over,
+    /// depending on --frontend-model
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub frontend_lag: u32,
+
+    /// |Δ| between market price and frontend average that tints the frontend line
+    #[arg(long, value_name = "PRICE", default_value_t = 1.0)]
+    pub diff_threshold: f64,
+
+    /// Render charts and borders with plain ASCII for terminals without Unicode
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// Keep panel titles to ASCII (`·` as `-`, `Δ` as `D`, …) for consoles
+    /// that draw other symbols as boxes or misalign borders around them;
+    /// on by default in Windows consoles outside Windows Terminal
+    #[arg(long)]
+    pub plain_titles: bool,
+
+    /// Chart plotting: braille, half-block, block or ascii, sharpest
+    /// first (default: braille, or half-block on the Linux console)
+    #[arg(long, value_enum, value_name = "RESOLUTION")]
+    pub resolution: Option<Resolution>,
+
+    /// Ticks per candle in the candle pane (adjust with `{` / `}`)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub candle_window: u32,
+
+    /// Hint in the footer when the backend chart plots more than this many
+    /// ticks per column, or fewer than one per this many columns (0 = never)
+    #[arg(long, value_name = "FACTOR", default_value_t = 4.0)]
+    pub density_warning: f64,
+
+    /// Plot prices on a logarithmic y-axis (toggle with `L`)
+    #[arg(long)]
+    pub log_scale: bool,
+
+    /// Ask for confirmation before `q` quits
+    #[arg(long)]
+    pub confirm_quit: bool,
+
+    /// Ticks back the `%Δ` column measures its change from
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 20,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub change_lookback: usize,
+
+    /// Simulated delay between placing a paper order and its fill
+    #[arg(long, value_name = "MS", default_value_t = 250)]
+    pub fill_latency: u64,
+
+    /// Flag a stock as FROZEN after this many ticks without any price change
+    #[arg(
+        long,
+        value_name = "TICKS",
+        default_value_t = 20,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub frozen_ticks: u64,
+
+    /// Debugging: corrupt this fraction of ticks (0–1) with NaN prices,
+    /// dropped ticks, frozen runs and negative prices, each logged to the
+    /// alerts panel, to check the guards against bad data
+    #[arg(long, value_name = "RATE", value_parser = parse_rate)]
+    pub inject_faults: Option<f64>,
+
+    /// Hold the charts still this long after the last crosshair, inspect or
+    /// candle-page key (Esc resumes sooner); 0 keeps them live
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "10s")]
+    pub inspect_hold: Duration,
+
+    /// Start in a preset market scenario (`M` cycles them live); it sets
+    /// the volatility multiplier, drift and jumps in place of --jump-rate
+    #[arg(long, value_enum, value_name = "SCENARIO")]
+    pub scenario: Option<Scenario>,
+
+    /// Gap each random-walk stock this many times a second on average, on
+    /// top of its usual moves (jump-diffusion; off by default)
+    #[arg(long, value_name = "PER_SECOND", value_parser = parse_jump_rate)]
+    pub jump_rate: Option<f64>,
+
+    /// Mean size of a --jump-rate gap, in percent of the price; each one
+    /// is up to twice this, up or down
+    #[arg(long, value_name = "PCT", default_value_t = 2.0)]
+    pub jump_size: f64,
+
+    /// Record a tick in the history only if it moves the price further than
+    /// this from the last recorded value; smaller moves repeat that value
+    #[arg(long, value_name = "PRICE", default_value_t = 0.0)]
+    pub noise_floor: f64,
+
+    /// Reject paper orders that would leave more than this many units long
+    /// or short in one stock
+    #[arg(long, value_name = "UNITS")]
+    pub max_position: Option<u32>,
+
+    /// Reject paper orders that would take the summed |position| × price
+    /// of all stocks over this
+    #[arg(long, value_name = "AMOUNT")]
+    pub max_notional: Option<f64>,
+
+    /// Plot the selected stock's rolling VWAP with bands K volume-weighted
+    /// standard deviations either side
+    #[arg(long, value_name = "K")]
+    pub vwap_bands: Option<f64>,
+
+    /// Show the pointers panel as a table of aligned columns (symbol, price,
+    /// Δ, average, σ, latency) instead of one free-form line per pointer
+    #[arg(long)]
+    pub table: bool,
+
+    /// Show the spread of stock A against stock B, raw and hedged by their
+    /// beta over the backend chart's window (e.g. 0,1)
+    #[arg(long, value_name = "A,B", value_parser = parse_pair)]
+    pub pair: Option<(usize, usize)>,
+
+    /// Tick at most this many stocks per hold of the market lock, letting
+    /// the renderer in between batches (default: all of them at once)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub update_batch: Option<u32>,
+
+    /// Money to risk per one-sigma tick move when sizing `B` / `S` orders
+    #[arg(long, value_name = "AMOUNT", default_value_t = 10.0)]
+    pub target_risk: f64,
+
+    /// Only redraw when the data changed or a key was pressed (at least once a second)
+    #[arg(long)]
+    pub redraw_on_change: bool,
+
+    /// Write avg/ewma/rsi/stddev of every stock after each of its ticks to a CSV
+    #[arg(long, value_name = "FILE")]
+    pub export_indicators: Option<PathBuf>,
+
+    /// On exit, write final price, session high/low, total return, max
+    /// drawdown, realized volatility and tick count per stock as JSON
+    #[arg(long, value_name = "FILE")]
+    pub summary: Option<PathBuf>,
+
+    /// When logged ticks reach disk: ticks:N (every N ticks), ms:T (every T
+    /// milliseconds) or exit (only on quit)
+    #[arg(
+        long,
+        value_name = "POLICY",
+        default_value = "ms:1000",
+        value_parser = parse_flush_policy
+    )]
+    pub tick_flush: FlushPolicy,
+
+    /// Save every stock's price, tick count and history here periodically,
+    /// keeping the previous few as FILE.1, FILE.2, …
+    #[arg(long, value_name = "FILE")]
+    pub checkpoint: Option<PathBuf>,
+
+    /// How often --checkpoint is written, e.g. 30s or 5m
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_positive_duration,
+        default_value = "60s",
+        requires = "checkpoint"
+    )]
+    pub checkpoint_interval: Duration,
+
+    /// Resume from a --checkpoint file (stocks are matched by symbol)
+    #[arg(long, value_name = "FILE")]
+    pub load_state: Option<PathBuf>,
+
+    /// Ring the terminal bell and send a desktop notification on new alerts
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Keep prices as whole multiples of this tick (e.g. 0.01), in integer arithmetic
+    #[arg(long, value_name = "PRICE")]
+    pub tick_size: Option<f64>,
+
+    /// Print periodic plain-text summaries for screen readers instead of the TUI
+    #[arg(long)]
+    pub accessible: bool,
+
+    /// Backend/frontend chart widths in percent, e.g. 60,40 (must sum to 100)
+    #[arg(
+        long,
+        value_name = "BACKEND,FRONTEND",
+        default_value = "50,50",
+        value_parser = parse_layout
+    )]
+    pub layout: (u16, u16),
+
+    /// Redraw rate cap; the rate eases down from it while prices are calm
+    #[arg(long, value_name = "FPS", default_value_t = 20.0)]
+    pub max_fps: f64,
+
+    /// Decimal places of prices, price differences and fills
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub price_decimals: usize,
+
+    /// Decimal places of the frontend moving average and median
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub avg_decimals: usize,
+
+    /// Decimal places of ratio indicators: β, skew, kurtosis, correlation
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub indicator_decimals: usize,
+
+    /// Decimal places of 0–100 readings: %Δ, book imbalance, RSI
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub percent_decimals: usize,
+
+    /// Trading hours on the simulated clock, e.g. 09:30-16:00; prices hold
+    /// at the last close outside them
+    #[arg(long, value_name = "HH:MM-HH:MM", value_parser = parse_session)]
+    pub session: Option<(u32, u32)>,
+
+    /// Simulated minutes that pass per tick with --session
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..=1440)
+    )]
+    pub tick_minutes: u32,
+
+    /// Extra indicator column in the pointers panel (repeatable)
+    #[arg(
+        long = "indicator",
+        value_name = "NAME",
+        value_parser = ["stddev", "drawdown", "median", "skew", "kurt"]
+    )]
+    pub indicators: Vec<String>,
+
+    /// Append every rendered frame to FILE as text, for `--play`
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Seed the price simulation's RNG instead of seeding it from the OS
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// Print a deterministic CSV of every indicator after each of STEPS
+    /// ticks per stock (from --seed, default 0) and exit, for diffing
+    /// against a known-good table
+    #[arg(long, value_name = "STEPS")]
+    pub golden: Option<u64>,
+
+    /// Show a `--record` file at its original pace and exit
+    #[arg(long, value_name = "FILE")]
+    pub play: Option<PathBuf>,
+
+    /// Quit on its own after this long, e.g. 90s, 5m or 1h
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub duration: Option<Duration>,
+}
+
+/// `--ipc-socket`, refused up front where there are no Unix sockets to
+/// serve it on.
+fn parse_socket_path(s: &str) -> Result<PathBuf, String> {
+    if cfg!(unix) {
+        Ok(PathBuf::from(s))
+    } else {
+        Err("Unix sockets are not available on this platform".to_string())
+    }
+}
+
+/// Whole seconds, minutes or hours with a `s` / `m` / `h` suffix; a bare
+/// number is seconds.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: u64 = number
+        .parse()
+        .map_err(|_| format!("{}: expected e.g. 30s, 5m or 1h", s))?;
+    let secs = match unit {
+        "s" => Some(n),
+        "m" => n.checked_mul(60),
+        "h" => n.checked_mul(3600),
+        _ => return Err(format!("{}: unknown unit {:?}, use s, m or h", s, unit)),
+    };
+    secs.map(Duration::from_secs).ok_or_else(|| format!("{}: too long", s))
+}
+
+/// `parse_duration`, for flags where zero would mean doing something
+/// continuously rather than never.
+fn parse_positive_duration(s: &str) -> Result<Duration, String> {
+    match parse_duration(s)? {
+        d if d.is_zero() => Err(format!("{}: must be longer than zero", s.trim())),
+        d => Ok(d),
+    }
+}
+
+/// `HH:MM-HH:MM` as minutes after midnight; a close before the open spans
+/// midnight.
+fn parse_session(s: &str) -> Result<(u32, u32), String> {
+    let (open, close) = s
+        .split_once('-')
+        .ok_or_else(|| "expected open-close, e.g. 09:30-16:00".to_string())?;
+    let minutes = |t: &str| -> Result<u32, String> {
+        let (h, m) = t
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| format!("{}: expected HH:MM", t))?;
+        let (h, m) = (
+            h.parse::<u32>().map_err(|e| format!("{}: {}", t, e))?,
+            m.parse::<u32>().map_err(|e| format!("{}: {}", t, e))?,
+        );
+        if h >= 24 || m >= 60 {
+            return Err(format!("{}: not a time of day", t));
+        }
+        Ok(h * 60 + m)
+    };
+    let (open, close) = (minutes(open)?, minutes(close)?);
+    if open == close {
+        return Err(format!("{}: open and close must differ", s));
+    }
+    Ok((open, close))
+}
+
+/// `ID:LOW-HIGH`; the id may be written in hex (`0x0`).
+fn parse_price_band(s: &str) -> Result<(usize, Band), String> {
+    let (id, range) = s
+        .split_once(':')
+        .ok_or_else(|| format!("{}: expected ID:LOW-HIGH, e.g. 0:90-110", s))?;
+    let id = match id.trim().strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => id.trim().parse(),
+    }
+    .map_err(|e| format!("{}: {}", id, e))?;
+    let (low, high) = parse_range(range)?;
+    Ok((id, Band { low, high }))
+}
+
+/// `LOW-HIGH` with `LOW < HIGH`; `LOW` may be negative.
+fn parse_range(range: &str) -> Result<(f64, f64), String> {
+    let dash = range
+        .get(1..)
+        .and_then(|r| r.find('-'))
+        .ok_or_else(|| format!("{}: expected LOW-HIGH", range))?;
+    let (low, high) = (&range[..dash + 1], &range[dash + 2..]);
+    let parse = |v: &str| v.trim().parse::<f64>().map_err(|e| format!("{}: {}", v, e));
+    let (low, high) = (parse(low)?, parse(high)?);
+    if !low.is_finite() || !high.is_finite() || low >= high {
+        return Err(format!("{}: expected finite edges, low below high", range));
+    }
+    Ok((low, high))
+}
+
+fn parse_jump_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!("{}: expected jumps per second above 0", s)),
+    }
+}
+
+fn parse_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate <= 1.0 => Ok(rate),
+        _ => Err(format!("{}: expected a fraction above 0, at most 1", s)),
+    }
+}
+
+fn parse_pair(s: &str) -> Result<(usize, usize), String> {
+    let usage = || format!("{}: expected two stock ids, e.g. 0,1", s);
+    let (a, b) = s.split_once(',').ok_or_else(usage)?;
+    let id = |v: &str| v.trim().parse::<usize>().map_err(|_| usage());
+    let (a, b) = (id(a)?, id(b)?);
+    if a == b {
+        return Err(format!("{}: a stock cannot be paired with itself", s));
+    }
+    Ok((a, b))
+}
+
+fn parse_heat_colors(s: &str) -> Result<(Rgb, Rgb), String> {
+    let (low, high) = s
+        .split_once(',')
+        .ok_or_else(|| "expected two hex colors, e.g. c00000,00a000".to_string())?;
+    let rgb = |hex: &str| -> Result<Rgb, String> {
+        let hex = hex.trim().trim_start_matches('#');
+        let bad = || format!("{}: expected six hex digits", hex);
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(bad());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| bad());
+        Ok([channel(0)?, channel(2)?, channel(4)?])
+    };
+    Ok((rgb(low)?, rgb(high)?))
+}
+
+fn parse_layout(s: &str) -> Result<(u16, u16), String> {
+    let (a, b) = s
+        .split_once(',')
+        .ok_or_else(|| "expected two percentages, e.g. 60,40".to_string())?;
+    let parse = |v: &str| v.trim().parse::<u16>().map_err(|e| format!("{}: {}", v, e));
+    let (a, b) = (parse(a)?, parse(b)?);
+    if a == 0 || b == 0 || u32::from(a) + u32::from(b) != 100 {
+        return Err(format!("{},{} must both be positive and sum to 100", a, b));
+    }
+    Ok((a, b))
+}
+
+impl Config {
+    pub fn allows_negative(&self, stock_id: usize) -> bool {
+        self.allow_negative.contains(&stock_id)
+    }
+
+    pub fn plot_points(&self) -> usize {
+        self.plot_points
+            .unwrap_or(self.history)
+            .clamp(2, self.history)
+    }
+
+    pub fn price_band(&self, stock_id: usize) -> Option<Band> {
+        self.price_bands
+            .iter()
+            .find(|(id, _)| *id == stock_id)
+            .map(|(_, band)| *band)
+    }
+
+    /// `--jump-rate` with its `--jump-size`, if jumps are on.
+    pub fn jumps(&self) -> Option<Jumps> {
+        self.jump_rate.map(|rate| Jumps {
+            rate,
+            size: self.jump_size.abs() / 100.0,
+        })
+    }
+
+    /// `--tick-size`, ignored unless positive.
+    pub fn tick_size(&self) -> Option<f64> {
+        self.tick_size.filter(|size| *size > 0.0)
+    }
+
+    pub fn tick_interval(&self, stock_id: usize) -> Duration {
+        let ms = self
+            .stock_rates
+            .get(stock_id)
+            .copied()
+            .unwrap_or(DEFAULT_TICK_MS);
+        Duration::from_millis(ms.max(1))
+    }
+}
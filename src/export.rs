@@ -0,0 +1,210 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Instant, SystemTime};
+
+use log::error;
+use serde::Serialize;
+
+use crate::clock::Zone;
+use crate::indicators;
+use crate::{MarketData, MOVING_AVG_LEN};
+
+/// Indicators of one stock right after one of its ticks.
+pub struct IndicatorRow {
+    at: SystemTime,
+    elapsed_ms: u128,
+    stock_id: usize,
+    price: f64,
+    avg: f64,
+    ewma: Option<f64>,
+    rsi: Option<f64>,
+    stddev: f64,
+}
+
+impl IndicatorRow {
+    pub fn new(md: &MarketData, price: f64, start: Instant) -> Self {
+        let history = &md.history;
+        let recent = &history[history.len().saturating_sub(MOVING_AVG_LEN)..];
+        Self {
+            at: SystemTime::now(),
+            elapsed_ms: start.elapsed().as_millis(),
+            stock_id: md.count,
+            price,
+            avg: indicators::mean(recent),
+            ewma: indicators::ewma(md.samples(), indicators::EWMA_ALPHA),
+            rsi: indicators::rsi(md.samples(), indicators::RSI_PERIOD),
+            stddev: indicators::stddev(history),
+        }
+    }
+}
+
+/// An indicator still warming up is left empty in the CSV.
+fn field(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Writes the last `len` ticks of one stock (what its zoomed chart shows) to
+/// `<symbol>_ticks_<first>-<last>.csv` in the working directory, each with
+/// the ISO-8601 time it was taken in `zone`. Seed history from before the
+/// first tick is left out.
+pub fn export_window(md: &MarketData, len: usize, zone: &Zone) -> io::Result<PathBuf> {
+    let len = len.min(md.ticks as usize + 1).min(md.history.len());
+    let window = &md.history[md.history.len() - len..];
+    let times = &md.times[md.times.len() - len..];
+    let first = md.ticks + 1 - len as u64;
+    let path = PathBuf::from(format!("{}_ticks_{}-{}.csv", md.symbol, first, md.ticks));
+
+    let now = SystemTime::now();
+    let mut out = BufWriter::new(File::create(&path)?);
+    writeln!(out, "tick,time,price")?;
+    for ((tick, price), at) in (first..).zip(window).zip(times) {
+        let time = zone.iso8601(now - at.elapsed());
+        writeln!(out, "{},{},{}", tick, time, price)?;
+    }
+    out.flush()?;
+    Ok(path)
+}
+
+/// Running figures over every in-session tick of a stock, unlike the
+/// bounded `history`, for the `--summary` report.
+#[derive(Clone, Default)]
+pub struct SessionStats {
+    open: Option<f64>,
+    last: f64,
+    high: f64,
+    low: f64,
+    peak: f64,
+    max_drawdown: f64,
+    /// Welford mean and sum of squared deviations of the per-tick returns.
+    returns: u64,
+    mean_return: f64,
+    return_m2: f64,
+}
+
+impl SessionStats {
+    /// Same drawdown and return conventions as `indicators::max_drawdown`
+    /// and `indicators::returns`, applied one price at a time.
+    pub fn record(&mut self, price: f64) {
+        if self.open.is_none() {
+            *self = Self {
+                open: Some(price),
+                last: price,
+                high: price,
+                low: price,
+                peak: price,
+                ..Self::default()
+            };
+            return;
+        }
+        let r = if self.last == 0.0 {
+            0.0
+        } else {
+            (price - self.last) / self.last
+        };
+        self.returns += 1;
+        let delta = r - self.mean_return;
+        self.mean_return += delta / self.returns as f64;
+        self.return_m2 += delta * (r - self.mean_return);
+
+        self.last = price;
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.peak = self.peak.max(price);
+        if self.peak > 0.0 {
+            self.max_drawdown = self.max_drawdown.max((self.peak - price) / self.peak);
+        }
+    }
+}
+
+/// One stock's entry in the `--summary` report. Ratios are fractions
+/// (0.01 = 1%).
+#[derive(Serialize)]
+struct StockSummary<'a> {
+    symbol: &'a str,
+    final_price: f64,
+    session_high: f64,
+    session_low: f64,
+    total_return: f64,
+    max_drawdown: f64,
+    /// Population stddev of the per-tick returns.
+    realized_volatility: f64,
+    ticks: u64,
+}
+
+/// Writes a JSON array with one `StockSummary` per stock to `path`. A stock
+/// that never ticked in session reports its current price throughout.
+pub fn write_summary(path: &Path, md_vec: &[MarketData]) -> io::Result<()> {
+    let summaries: Vec<StockSummary> = md_vec
+        .iter()
+        .map(|md| {
+            let s = &md.stats;
+            let price = *md.price.read().unwrap();
+            let (open, last, high, low) = match s.open {
+                Some(open) => (open, s.last, s.high, s.low),
+                None => (price, price, price, price),
+            };
+            StockSummary {
+                symbol: &md.symbol,
+                final_price: last,
+                session_high: high,
+                session_low: low,
+                total_return: if open == 0.0 {
+                    0.0
+                } else {
+                    (last - open) / open
+                },
+                max_drawdown: s.max_drawdown,
+                realized_volatility: if s.returns < 2 {
+                    0.0
+                } else {
+                    (s.return_m2 / s.returns as f64).sqrt()
+                },
+                ticks: md.ticks,
+            }
+        })
+        .collect();
+    let mut out = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut out, &summaries)?;
+    writeln!(out)?;
+    out.flush()
+}
+
+/// Starts a thread appending every row it receives to a CSV at `path`, so
+/// the updater never waits on disk. Rows are stamped in `zone`.
+pub fn spawn_indicator_writer(path: &Path, zone: Zone) -> io::Result<Sender<IndicatorRow>> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "time,elapsed_ms,stock_id,price,avg,ewma,rsi,stddev")?;
+
+    let (tx, rx) = mpsc::channel::<IndicatorRow>();
+    thread::spawn(move || {
+        // Write whatever has queued up, then flush, so the file stays current
+        // even though the process exits without dropping the sender
+        while let Ok(first) = rx.recv() {
+            let result = std::iter::once(first)
+                .chain(rx.try_iter())
+                .try_for_each(|row| {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{},{}",
+                        zone.iso8601(row.at),
+                        row.elapsed_ms,
+                        row.stock_id,
+                        row.price,
+                        row.avg,
+                        field(row.ewma),
+                        field(row.rsi),
+                        row.stddev
+                    )
+                })
+                .and_then(|_| out.flush());
+            if let Err(e) = result {
+                error!("Indicator export failed: {:?}", e);
+                return;
+            }
+        }
+    });
+    Ok(tx)
+}
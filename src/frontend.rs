@@ -0,0 +1,63 @@
+//! How the frontend value trails the market price (`--frontend-model`),
+//! the lag the whole market-vs-frontend comparison is about.
+
+use clap::ValueEnum;
+
+use crate::chart::tail;
+use crate::indicators;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FrontendModel {
+    /// Mean of the last --frontend-lag ticks (the original behaviour).
+    Avg,
+    /// The price exactly --frontend-lag ticks ago.
+    Delay,
+    /// Exponential low-pass with a span of --frontend-lag refreshes.
+    LowPass,
+    /// The latest price, picked up only every --frontend-lag-th refresh.
+    Hold,
+}
+
+impl FrontendModel {
+    /// Value after refresh number `refresh` (counting from 0), given the
+    /// market history and the value shown until now.
+    pub fn next(self, history: &[f64], previous: f64, refresh: u64, lag: usize) -> f64 {
+        let Some(&price) = history.last() else {
+            return previous;
+        };
+        match self {
+            FrontendModel::Avg => indicators::mean(tail(history, lag)),
+            FrontendModel::Delay => delayed(history, lag),
+            FrontendModel::LowPass => low_pass(previous, price, lag),
+            FrontendModel::Hold => sample_and_hold(previous, price, refresh, lag),
+        }
+    }
+
+    /// Name of the frontend value in titles and pointer lines.
+    pub fn label(self) -> &'static str {
+        match self {
+            FrontendModel::Avg => "moving avg",
+            FrontendModel::Delay => "delayed",
+            FrontendModel::LowPass => "low-pass",
+            FrontendModel::Hold => "held",
+        }
+    }
+}
+
+/// Oldest sample while the history is still shorter than the delay.
+fn delayed(history: &[f64], lag: usize) -> f64 {
+    history[history.len().saturating_sub(lag + 1)]
+}
+
+fn low_pass(previous: f64, price: f64, lag: usize) -> f64 {
+    let alpha = 2.0 / (lag as f64 + 1.0);
+    previous + alpha * (price - previous)
+}
+
+fn sample_and_hold(previous: f64, price: f64, refresh: u64, lag: usize) -> f64 {
+    if refresh.is_multiple_of(lag as u64) {
+        price
+    } else {
+        previous
+    }
+}
@@ -0,0 +1,102 @@
+//! Deterministic indicator table (`--golden STEPS`), for checking indicator
+//! changes against a known-good output: the same seed, instruments and
+//! flags always print the same bytes.
+//!
+//! Unlike the TUI, the walk here steps every stock once per tick in stock
+//! order with nothing else drawing on the RNG, so no timing gets in.
+
+use std::io::{self, Write};
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config::Config;
+use crate::instruments::Instrument;
+use crate::{indicators, sim, Eviction, MarketData, MOVING_AVG_LEN};
+
+const HEADER: &str =
+    "tick,stock_id,price,avg,ewma,rsi,stddev,drawdown,skew,kurtosis,zscore,acceleration";
+
+/// Walks every stock `steps` ticks from `seed` and writes one CSV row of
+/// indicators per stock per tick, numbers in full precision. Indicators
+/// still warming up are left empty, as in `--export-indicators`.
+pub fn run(
+    config: &Config,
+    instruments: &[Instrument],
+    seed: u64,
+    steps: u64,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut md_vec: Vec<MarketData> = instruments
+        .iter()
+        .enumerate()
+        .map(|(i, inst)| MarketData::new(i, inst, config))
+        .collect();
+    let at = Instant::now();
+    writeln!(out, "{}", HEADER)?;
+    for tick in 1..=steps {
+        for md in &mut md_vec {
+            let mut price = *md.price.read().unwrap();
+            let delta = rng.gen_range(-md.sigma..=md.sigma);
+            sim::shift_price(&mut price, &mut md.grid, delta, md.allow_negative, md.band);
+            *md.price.write().unwrap() = price;
+            md.ticks = tick;
+            md.push_sample(price, 0, at, Eviction::Count(config.history));
+            writeln!(out, "{}", row(md, price))?;
+        }
+    }
+    out.flush()
+}
+
+fn row(md: &MarketData, price: f64) -> String {
+    let history = &md.history;
+    let samples = md.samples();
+    let returns = indicators::returns(samples);
+    let field = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{}",
+        md.ticks,
+        md.count,
+        price,
+        indicators::mean(&history[history.len().saturating_sub(MOVING_AVG_LEN)..]),
+        field(indicators::ewma(samples, indicators::EWMA_ALPHA)),
+        field(indicators::rsi(samples, indicators::RSI_PERIOD)),
+        indicators::stddev(history),
+        indicators::max_drawdown(history),
+        indicators::skewness(&returns),
+        indicators::kurtosis(&returns),
+        indicators::zscore(samples),
+        indicators::acceleration(samples),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    use crate::instruments;
+
+    fn table(seed: u64, steps: u64) -> String {
+        let config = Config::parse_from(["rust_hft_tui", "--history", "50"]);
+        let mut out = vec![];
+        run(&config, &instruments::defaults(), seed, steps, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn same_seed_prints_the_same_table() {
+        assert_eq!(table(7, 30), table(7, 30));
+        assert_ne!(table(7, 30), table(8, 30));
+    }
+
+    /// Regenerate with
+    /// `cargo run -- --seed 7 --history 50 --golden 30 > testdata/golden_seed7.csv`
+    /// after a deliberate change to an indicator, and review the diff.
+    #[test]
+    fn matches_the_golden_file() {
+        assert_eq!(table(7, 30), include_str!("../testdata/golden_seed7.csv"));
+    }
+}
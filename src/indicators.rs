@@ -0,0 +1,626 @@
+//! Pure indicator math over price windows. Shared by the TUI and every
+//! exporter so the numbers always agree. Indicators with a look-back return
+//! `None` until their window holds enough samples to mean anything.
+
+use std::time::Instant;
+
+/// Smoothing factor used for the displayed/exported EWMA.
+pub const EWMA_ALPHA: f64 = 0.2;
+/// Look-back (in price changes) of the RSI.
+pub const RSI_PERIOD: usize = 14;
+/// Ticks each point of the rolling VWAP bands is taken over.
+pub const VWAP_WINDOW: usize = 20;
+
+pub fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Middle value of the window (mean of the two middle ones for an even
+/// length). Unlike the mean it barely moves on a single spike.
+pub fn rolling_median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Samples an EWMA needs before the seed stops dominating: the span of the
+/// equivalent simple average, `2 / alpha - 1`.
+pub fn ewma_warmup(alpha: f64) -> usize {
+    (2.0 / alpha - 1.0).ceil().max(1.0) as usize
+}
+
+/// Exponentially weighted moving average, seeded with the first value.
+/// `None` for fewer than `ewma_warmup(alpha)` values.
+pub fn ewma(values: &[f64], alpha: f64) -> Option<f64> {
+    if values.len() < ewma_warmup(alpha) {
+        return None;
+    }
+    let (&first, rest) = values.split_first()?;
+    Some(
+        rest.iter()
+            .fold(first, |acc, &v| alpha * v + (1.0 - alpha) * acc),
+    )
+}
+
+/// Population standard deviation.
+pub fn stddev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    let var = values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / values.len() as f64;
+    var.sqrt()
+}
+
+/// How many standard deviations the last value sits from the window's
+/// mean; 0 for an empty or flat window.
+pub fn zscore(values: &[f64]) -> f64 {
+    let sd = stddev(values);
+    match values.last() {
+        Some(last) if sd > 0.0 => (last - mean(values)) / sd,
+        _ => 0.0,
+    }
+}
+
+/// Relative strength index over the last `period` price changes, in 0..=100.
+/// A flat window reads as a neutral 50; `None` until `period` changes exist.
+pub fn rsi(values: &[f64], period: usize) -> Option<f64> {
+    if values.len() < period + 1 {
+        return None;
+    }
+    let start = values.len() - (period + 1);
+    let (mut gains, mut losses) = (0.0, 0.0);
+    for w in values[start..].windows(2) {
+        let d = w[1] - w[0];
+        if d > 0.0 {
+            gains += d;
+        } else {
+            losses -= d;
+        }
+    }
+
+    if gains + losses == 0.0 {
+        Some(50.0)
+    } else {
+        Some(100.0 * gains / (gains + losses))
+    }
+}
+
+/// Largest peak-to-trough fall as a fraction of the peak (0.1 = 10%).
+/// Non-positive peaks are skipped since a percentage fall is undefined there.
+pub fn max_drawdown(values: &[f64]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut worst: f64 = 0.0;
+    for &v in values {
+        peak = peak.max(v);
+        if peak > 0.0 {
+            worst = worst.max((peak - v) / peak);
+        }
+    }
+    worst
+}
+
+/// Third standardized moment: positive when the right tail is longer.
+/// Zero for fewer than three values or a zero-variance window.
+pub fn skewness(returns: &[f64]) -> f64 {
+    standardized_moment(returns, 3).unwrap_or(0.0)
+}
+
+/// Excess kurtosis (fourth standardized moment minus 3), so normal returns
+/// read about 0 and Laplace ones about 3. Zero for fewer than three values
+/// or a zero-variance window.
+pub fn kurtosis(returns: &[f64]) -> f64 {
+    standardized_moment(returns, 4).map_or(0.0, |k| k - 3.0)
+}
+
+fn standardized_moment(values: &[f64], order: i32) -> Option<f64> {
+    // Equal values would otherwise divide rounding noise by rounding noise
+    if values.len() < 3 || values.iter().all(|&v| v == values[0]) {
+        return None;
+    }
+    let sd = stddev(values);
+    let m = mean(values);
+    let moment = values.iter().map(|v| (v - m).powi(order)).sum::<f64>() / values.len() as f64;
+    Some(moment / sd.powi(order))
+}
+
+/// `(bid - ask) / (bid + ask)` in -1..=1: positive when more size rests on
+/// the bid. Zero for an empty book.
+pub fn imbalance(bid_size: f64, ask_size: f64) -> f64 {
+    let total = bid_size + ask_size;
+    if total == 0.0 {
+        0.0
+    } else {
+        (bid_size - ask_size) / total
+    }
+}
+
+/// Percent change of the last value over the one `lookback` steps earlier,
+/// or over the oldest value when the window is shorter. Zero from a zero base;
+/// measured against the base's magnitude so a rise reads positive even below
+/// zero.
+pub fn pct_change(values: &[f64], lookback: usize) -> f64 {
+    let Some(&last) = values.last() else {
+        return 0.0;
+    };
+    let base = values[values.len().saturating_sub(lookback + 1)];
+    if base == 0.0 {
+        0.0
+    } else {
+        (last - base) / base.abs() * 100.0
+    }
+}
+
+/// Simple per-step returns `(p1 - p0) / p0`; a step from a zero price
+/// counts as no return.
+pub fn returns(prices: &[f64]) -> Vec<f64> {
+    prices
+        .windows(2)
+        .map(|w| {
+            if w[0] == 0.0 {
+                0.0
+            } else {
+                (w[1] - w[0]) / w[0]
+            }
+        })
+        .collect()
+}
+
+/// Log returns `ln(p1 / p0)`, which add up over consecutive steps. A step
+/// to or from a non-positive price (spreads may go negative) has no log and
+/// counts as no return.
+pub fn log_returns(prices: &[f64]) -> Vec<f64> {
+    prices
+        .windows(2)
+        .map(|w| {
+            if w[0] > 0.0 && w[1] > 0.0 {
+                (w[1] / w[0]).ln()
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Which returns the return-based figures are taken over (`N`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReturnKind {
+    /// `returns`
+    #[default]
+    Simple,
+    /// `log_returns`
+    Log,
+}
+
+impl ReturnKind {
+    pub fn of(self, prices: &[f64]) -> Vec<f64> {
+        match self {
+            ReturnKind::Simple => returns(prices),
+            ReturnKind::Log => log_returns(prices),
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            ReturnKind::Simple => ReturnKind::Log,
+            ReturnKind::Log => ReturnKind::Simple,
+        }
+    }
+}
+
+/// Mean change between consecutive returns: positive while moves speed up,
+/// negative while they slow. Zero for fewer than three prices.
+pub fn acceleration(prices: &[f64]) -> f64 {
+    let returns = returns(prices);
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let diffs: Vec<f64> = returns.windows(2).map(|w| w[1] - w[0]).collect();
+    mean(&diffs)
+}
+
+/// Time-weighted average price: each price counts for as long as it stood,
+/// i.e. until the next sample, so a burst of fast ticks does not outweigh
+/// a long quiet stretch. The newest price has not stood yet and carries no
+/// weight; with no time elapsed at all this is the plain mean.
+pub fn twap(samples: &[(Instant, f64)]) -> f64 {
+    let mut weighted = 0.0;
+    let mut total = 0.0;
+    for pair in samples.windows(2) {
+        let ((start, price), (end, _)) = (pair[0], pair[1]);
+        let secs = end.saturating_duration_since(start).as_secs_f64();
+        weighted += price * secs;
+        total += secs;
+    }
+    if total > 0.0 {
+        weighted / total
+    } else {
+        mean(&samples.iter().map(|(_, p)| *p).collect::<Vec<_>>())
+    }
+}
+
+/// Volume-weighted average price with bands `k` volume-weighted standard
+/// deviations either side, as `(lower, vwap, upper)`. `prices` and
+/// `volumes` pair up index for index. With no volume at all every price
+/// counts the same, so it reads as the plain mean ± `k`·stddev.
+pub fn vwap_bands(prices: &[f64], volumes: &[u64], k: f64) -> (f64, f64, f64) {
+    let pairs = prices.iter().zip(volumes);
+    let total: u64 = pairs.clone().map(|(_, &v)| v).sum();
+    if total == 0 {
+        let (m, sd) = (mean(prices), stddev(prices));
+        return (m - k * sd, m, m + k * sd);
+    }
+    let total = total as f64;
+    let vwap = pairs.clone().map(|(p, &v)| p * v as f64).sum::<f64>() / total;
+    let var = pairs
+        .map(|(p, &v)| v as f64 * (p - vwap).powi(2))
+        .sum::<f64>()
+        / total;
+    let sd = var.sqrt();
+    (vwap - k * sd, vwap, vwap + k * sd)
+}
+
+/// Open, high, low and close of a run of samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candle {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// One candle per `window` consecutive samples from the start of `values`;
+/// the last one is partial while its window is still filling.
+pub fn to_candles(values: &[f64], window: usize) -> Vec<Candle> {
+    values
+        .chunks(window.max(1))
+        .map(|chunk| Candle {
+            open: chunk[0],
+            high: chunk.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            low: chunk.iter().copied().fold(f64::INFINITY, f64::min),
+            close: chunk[chunk.len() - 1],
+        })
+        .collect()
+}
+
+/// Equal-weighted basket of several price series, aligned on their most
+/// recent samples and truncated to the shortest one.
+pub fn basket_index(series: &[&[f64]]) -> Vec<f64> {
+    let len = series.iter().map(|s| s.len()).min().unwrap_or(0);
+    (0..len)
+        .map(|i| {
+            let sum: f64 = series.iter().map(|s| s[s.len() - len + i]).sum();
+            sum / series.len() as f64
+        })
+        .collect()
+}
+
+/// Covariance of asset with index returns over the index variance, using
+/// the overlapping most recent samples. Zero when the index is flat.
+pub fn beta(asset_returns: &[f64], index_returns: &[f64]) -> f64 {
+    let n = asset_returns.len().min(index_returns.len());
+    if n < 2 {
+        return 0.0;
+    }
+    let a = &asset_returns[asset_returns.len() - n..];
+    let b = &index_returns[index_returns.len() - n..];
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let cov = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| (x - mean_a) * (y - mean_b))
+        .sum::<f64>();
+    let var = b.iter().map(|y| (y - mean_b).powi(2)).sum::<f64>();
+    if var == 0.0 {
+        0.0
+    } else {
+        cov / var
+    }
+}
+
+/// A pair's spread over its window, hedged and not.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spread {
+    /// Units of `b` hedging one of `a`: the `beta` of `a`'s prices on
+    /// `b`'s over the window, so `a - beta·b` moves as little with `b` as
+    /// the window allows.
+    pub beta: f64,
+    /// The newest `a - b`.
+    pub raw: f64,
+    /// The newest `a - beta·b`.
+    pub hedged: f64,
+}
+
+/// The spread of the overlapping most recent prices of `a` and `b`, or
+/// None with no overlap. A flat `b` hedges with a beta of zero.
+pub fn hedged_spread(a: &[f64], b: &[f64]) -> Option<Spread> {
+    let (&last_a, &last_b) = (a.last()?, b.last()?);
+    let beta = beta(a, b);
+    Some(Spread {
+        beta,
+        raw: last_a - last_b,
+        hedged: last_a - beta * last_b,
+    })
+}
+
+/// Pearson correlation over the overlapping most recent samples, in
+/// -1..=1. Zero when either side is flat.
+pub fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return 0.0;
+    }
+    let a = &a[a.len() - n..];
+    let b = &b[b.len() - n..];
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let cov = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| (x - mean_a) * (y - mean_b))
+        .sum::<f64>();
+    let var_a = a.iter().map(|x| (x - mean_a).powi(2)).sum::<f64>();
+    let var_b = b.iter().map(|y| (y - mean_b).powi(2)).sum::<f64>();
+    if var_a == 0.0 || var_b == 0.0 {
+        0.0
+    } else {
+        (cov / (var_a * var_b).sqrt()).clamp(-1.0, 1.0)
+    }
+}
+
+/// Pairwise `pearson` of every series against every other, row `i` column
+/// `j` pairing series `i` with `j`. Each pair is computed once.
+pub fn correlation_matrix(series: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = series.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let r = pearson(&series[i], &series[j]);
+            matrix[i][j] = r;
+            matrix[j][i] = r;
+        }
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_of_odd_length_is_middle_value() {
+        assert_eq!(rolling_median(&[3.0, 100.0, 1.0, 2.0, 4.0]), 3.0);
+    }
+
+    #[test]
+    fn median_of_even_length_averages_middle_pair() {
+        assert_eq!(rolling_median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+    }
+
+    #[test]
+    fn median_of_empty_is_zero() {
+        assert_eq!(rolling_median(&[]), 0.0);
+    }
+
+    #[test]
+    fn rsi_waits_for_a_full_period() {
+        let prices: Vec<f64> = (0..=RSI_PERIOD).map(|i| i as f64).collect();
+        assert_eq!(rsi(&prices[1..], RSI_PERIOD), None);
+        assert_eq!(rsi(&prices, RSI_PERIOD), Some(100.0));
+    }
+
+    #[test]
+    fn ewma_waits_for_its_span() {
+        let warmup = ewma_warmup(EWMA_ALPHA);
+        assert_eq!(warmup, 9);
+        assert_eq!(ewma(&vec![1.0; warmup - 1], EWMA_ALPHA), None);
+        assert_eq!(ewma(&vec![1.0; warmup], EWMA_ALPHA), Some(1.0));
+    }
+
+    #[test]
+    fn symmetric_returns_have_zero_skew() {
+        assert!(skewness(&[-2.0, -1.0, 0.0, 1.0, 2.0]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn long_right_tail_skews_positive() {
+        assert!(skewness(&[0.0, 0.0, 0.0, 0.0, 10.0]) > 0.0);
+    }
+
+    #[test]
+    fn kurtosis_of_symmetric_set_is_exact() {
+        // Fourth moment 6.8 over squared variance 2^2, minus 3
+        assert!((kurtosis(&[-2.0, -1.0, 0.0, 1.0, 2.0]) - (1.7 - 3.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn moments_of_flat_returns_are_zero() {
+        assert_eq!(skewness(&[0.01; 10]), 0.0);
+        assert_eq!(kurtosis(&[0.01; 10]), 0.0);
+    }
+
+    #[test]
+    fn acceleration_of_steadily_faster_moves_is_the_step() {
+        let mut prices = vec![100.0];
+        for i in 0..10 {
+            let r = 0.01 + 0.002 * i as f64;
+            prices.push(prices.last().unwrap() * (1.0 + r));
+        }
+        assert!((acceleration(&prices) - 0.002).abs() < 1e-12);
+    }
+
+    #[test]
+    fn acceleration_needs_two_returns() {
+        assert_eq!(acceleration(&[100.0, 101.0]), 0.0);
+    }
+
+    #[test]
+    fn zscore_measures_the_last_value_in_standard_deviations() {
+        // mean 3, population stddev 2
+        let z = zscore(&[1.0, 1.0, 5.0, 5.0]);
+        assert!((z - 1.0).abs() < 1e-12);
+        assert!((zscore(&[5.0, 5.0, 1.0, 1.0]) + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zscore_of_a_flat_or_empty_window_is_zero() {
+        assert_eq!(zscore(&[2.0, 2.0, 2.0]), 0.0);
+        assert_eq!(zscore(&[7.0]), 0.0);
+        assert_eq!(zscore(&[]), 0.0);
+    }
+
+    #[test]
+    fn twap_weights_prices_by_how_long_they_stood() {
+        let t = Instant::now();
+        let at = |ms| t + std::time::Duration::from_millis(ms);
+        // 100 for 3s, 110 for 1s; 200 is the newest and has not stood yet
+        let samples = [(at(0), 100.0), (at(3000), 110.0), (at(4000), 200.0)];
+        assert!((twap(&samples) - 102.5).abs() < 1e-9);
+        assert_eq!(twap(&[(t, 1.0), (t, 3.0)]), 2.0);
+    }
+
+    #[test]
+    fn vwap_bands_weight_by_volume() {
+        // 3 units at 10, 1 at 14: vwap 11, variance (3·1 + 1·9) / 4 = 3
+        let (lower, vwap, upper) = vwap_bands(&[10.0, 14.0], &[3, 1], 2.0);
+        assert!((vwap - 11.0).abs() < 1e-12);
+        assert!((upper - vwap - 2.0 * 3f64.sqrt()).abs() < 1e-12);
+        assert!((vwap - lower - 2.0 * 3f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn vwap_bands_without_volume_fall_back_to_the_mean() {
+        // mean 3, population stddev 2
+        assert_eq!(
+            vwap_bands(&[1.0, 1.0, 5.0, 5.0], &[0; 4], 1.0),
+            (1.0, 3.0, 5.0)
+        );
+        assert_eq!(vwap_bands(&[], &[], 2.0), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn candles_split_on_the_window_with_a_partial_last() {
+        let candles = to_candles(&[1.0, 3.0, 2.0, 5.0, 4.0], 2);
+        assert_eq!(candles.len(), 3);
+        assert_eq!(
+            candles[1],
+            Candle {
+                open: 2.0,
+                high: 5.0,
+                low: 2.0,
+                close: 5.0
+            }
+        );
+        assert_eq!(candles[2].open, candles[2].close);
+    }
+
+    #[test]
+    fn log_returns_add_up_over_steps() {
+        let prices = [100.0, 110.0, 99.0, 121.0];
+        let total: f64 = log_returns(&prices).iter().sum();
+        assert!((total - (121.0f64 / 100.0).ln()).abs() < 1e-12);
+        assert!((log_returns(&[100.0, 110.0])[0] - 1.1f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn log_returns_skip_non_positive_prices() {
+        assert_eq!(log_returns(&[1.0, 0.0, 2.0, -1.0, 1.0]), vec![0.0; 4]);
+        assert_eq!(log_returns(&[5.0]), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn hedged_spread_removes_the_common_move() {
+        // `a` moves twice as much as `b`: a raw spread trends, the hedged one holds
+        let b = [50.0, 51.0, 49.0, 52.0, 50.5];
+        let a: Vec<f64> = b.iter().map(|p| 10.0 + 2.0 * p).collect();
+        let spread = hedged_spread(&a, &b).unwrap();
+        assert!((spread.beta - 2.0).abs() < 1e-12);
+        assert!((spread.raw - (111.0 - 50.5)).abs() < 1e-12);
+        assert!((spread.hedged - 10.0).abs() < 1e-9);
+        assert_eq!(hedged_spread(&a, &[]), None);
+    }
+
+    #[test]
+    fn beta_of_index_against_itself_is_one() {
+        let index = returns(&[100.0, 101.0, 99.5, 102.0, 101.0, 103.5]);
+        assert!((beta(&index, &index) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn beta_scales_with_leverage() {
+        let index = returns(&[100.0, 101.0, 99.5, 102.0, 101.0, 103.5]);
+        let levered: Vec<f64> = index.iter().map(|r| 2.0 * r).collect();
+        assert!((beta(&levered, &index) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn beta_against_flat_index_is_zero() {
+        let asset = returns(&[100.0, 101.0, 99.0, 102.0]);
+        let flat = returns(&[50.0; 4]);
+        assert_eq!(beta(&asset, &flat), 0.0);
+    }
+
+    #[test]
+    fn pct_change_rises_positive_from_a_negative_base() {
+        assert_eq!(pct_change(&[-10.0, -5.0], 1), 50.0);
+        assert_eq!(pct_change(&[-10.0, -15.0], 1), -50.0);
+    }
+
+    #[test]
+    fn pct_change_from_zero_base_is_zero() {
+        assert_eq!(pct_change(&[0.0, 5.0], 1), 0.0);
+    }
+
+    #[test]
+    fn pct_change_falls_back_to_oldest_value_in_short_window() {
+        assert_eq!(pct_change(&[100.0, 90.0, 110.0], 10), 10.0);
+        assert_eq!(pct_change(&[], 10), 0.0);
+    }
+
+    #[test]
+    fn pearson_of_linear_series_is_plus_or_minus_one() {
+        let a = [1.0, 2.0, 3.0, 5.0, 8.0];
+        let up: Vec<f64> = a.iter().map(|x| 3.0 * x + 1.0).collect();
+        let down: Vec<f64> = a.iter().map(|x| -2.0 * x).collect();
+        assert!((pearson(&a, &up) - 1.0).abs() < 1e-12);
+        assert!((pearson(&a, &down) + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pearson_against_a_flat_series_is_zero() {
+        assert_eq!(pearson(&[1.0, 2.0, 4.0], &[5.0; 3]), 0.0);
+    }
+
+    #[test]
+    fn pearson_aligns_unequal_lengths_on_the_tail() {
+        let long = [100.0, -50.0, 1.0, 2.0, 3.0];
+        assert!((pearson(&long, &[2.0, 4.0, 6.0]) - 1.0).abs() < 1e-12);
+        assert!((pearson(&[2.0, 4.0, 6.0], &long) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn correlation_matrix_is_symmetric_with_unit_diagonal() {
+        let series = vec![
+            vec![1.0, 2.0, 3.0, 2.5],
+            vec![4.0, 1.0, 0.5, 2.0],
+            vec![0.0, 1.0, 1.5, 3.0],
+        ];
+        let matrix = correlation_matrix(&series);
+        for (i, row) in matrix.iter().enumerate() {
+            assert!((row[i] - 1.0).abs() < 1e-12);
+            for (j, r) in row.iter().enumerate() {
+                assert_eq!(*r, matrix[j][i]);
+            }
+        }
+    }
+}
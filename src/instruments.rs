@@ -0,0 +1,73 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// Stocks simulated when no `--instruments` file is given.
+pub const DEFAULT_INSTRUMENTS: usize = 3;
+
+/// One entry of the `--instruments` JSON array. Every field is optional.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Instrument {
+    /// Short ticker shown in panels and legends, e.g. `AAPL`.
+    pub symbol: String,
+    pub name: String,
+    pub initial_price: f64,
+    /// Half-width of the uniform per-tick price shock.
+    pub sigma: f64,
+}
+
+impl Default for Instrument {
+    fn default() -> Self {
+        Self {
+            symbol: String::new(),
+            name: String::new(),
+            initial_price: 100.0,
+            sigma: 2.0,
+        }
+    }
+}
+
+/// Reads a JSON array of instruments. Entries without a symbol are named
+/// after their position, the same way the built-in stocks are, and a
+/// negative sigma is taken as its magnitude.
+pub fn load(path: &Path) -> io::Result<Vec<Instrument>> {
+    let text = fs::read_to_string(path)?;
+    let instruments: Vec<Instrument> =
+        serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if instruments.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "instrument file lists no instruments",
+        ));
+    }
+    Ok(normalize(instruments))
+}
+
+/// A default instrument named after its position.
+pub fn numbered(i: usize) -> Instrument {
+    Instrument {
+        symbol: default_symbol(i),
+        ..Instrument::default()
+    }
+}
+
+fn default_symbol(i: usize) -> String {
+    format!("Stock {}", i)
+}
+
+pub fn defaults() -> Vec<Instrument> {
+    normalize(vec![Instrument::default(); DEFAULT_INSTRUMENTS])
+}
+
+fn normalize(mut instruments: Vec<Instrument>) -> Vec<Instrument> {
+    for (i, inst) in instruments.iter_mut().enumerate() {
+        if inst.symbol.is_empty() {
+            inst.symbol = default_symbol(i);
+        }
+        inst.sigma = inst.sigma.abs();
+    }
+    instruments
+}
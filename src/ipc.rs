@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use log::{error, info};
+use serde::Serialize;
+
+use crate::indicators;
+use crate::{MarketData, MOVING_AVG_LEN};
+
+#[derive(Serialize)]
+struct StockSnapshot {
+    symbol: String,
+    price: f64,
+    avg: f64,
+    /// `null` while still warming up.
+    ewma: Option<f64>,
+    rsi: Option<f64>,
+    stddev: f64,
+    drawdown: f64,
+}
+
+/// Indicators for every stock keyed by stock id, computed from the current
+/// history. Only called when a client connects, never from the updater.
+fn snapshot(md_vec: &[MarketData]) -> BTreeMap<usize, StockSnapshot> {
+    md_vec
+        .iter()
+        .map(|md| {
+            let history = &md.history;
+            let recent = &history[history.len().saturating_sub(MOVING_AVG_LEN)..];
+            let snap = StockSnapshot {
+                symbol: md.symbol.clone(),
+                price: *md.price.read().unwrap(),
+                avg: indicators::mean(recent),
+                ewma: indicators::ewma(md.samples(), indicators::EWMA_ALPHA),
+                rsi: indicators::rsi(md.samples(), indicators::RSI_PERIOD),
+                stddev: indicators::stddev(history),
+                drawdown: indicators::max_drawdown(history),
+            };
+            (md.count, snap)
+        })
+        .collect()
+}
+
+/// Serves a JSON snapshot on a Unix socket: each connection receives one
+/// document and is closed, so `socat - UNIX-CONNECT:<path>` is enough to poll.
+pub fn spawn_server(path: &Path, market_data: Arc<RwLock<Vec<MarketData>>>) -> io::Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    info!("Serving snapshots on {}", path.display());
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(s) => s,
+                Err(e) => {
+                    error!("IPC accept error: {:?}", e);
+                    continue;
+                }
+            };
+            let md_vec = market_data.read().unwrap().clone();
+            let body = serde_json::to_vec(&snapshot(&md_vec));
+            let result = body
+                .map_err(io::Error::from)
+                .and_then(|b| stream.write_all(&b).and_then(|_| stream.write_all(b"\n")));
+            if let Err(e) = result {
+                error!("IPC write error: {:?}", e);
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,563 @@
+//! The keymap: one table that both dispatches key presses and renders the
+//! `?` help and the footer hints, so neither can drift from the other.
+
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Everything a key can do. Handled in `main`, which owns the shared state
+/// most of them touch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    /// Any of `0`–`9`; `main` reads which from the key.
+    JumpDigit,
+    SelectNext,
+    SelectPrev,
+    ToggleDetail,
+    ToggleMultiTimeframe,
+    ToggleFade,
+    CycleZoom,
+    ToggleOverlay,
+    ToggleReturns,
+    ToggleLogReturns,
+    ToggleAbsoluteX,
+    SplitHorizontal,
+    SplitVertical,
+    Buy,
+    Sell,
+    BuySized,
+    SellSized,
+    Export,
+    ToggleSmoothLatency,
+    ResetLatency,
+    CycleTimeUnit,
+    CycleIndicatorPage,
+    NewsShock,
+    VolDown,
+    VolUp,
+    TogglePause,
+    ToggleHelp,
+    CrosshairLeft,
+    CrosshairRight,
+    ToggleLogScale,
+    ClosePane,
+    FocusNextPane,
+    CyclePaneContent,
+    ToggleTicks,
+    Snapshot,
+    ToggleCorrelation,
+    Step,
+    ReplaySlower,
+    ReplayFaster,
+    NarrowerCandles,
+    WiderCandles,
+    AddStock,
+    RemoveStock,
+    ToggleTicker,
+    ToggleYLock,
+    ToggleInspect,
+    ToggleCandleGrid,
+    NextCandlePage,
+    PrevCandlePage,
+    ResumeLive,
+    PlaybackBack,
+    PlaybackForward,
+    OpenCommand,
+    CycleScenario,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub ctrl: bool,
+    /// Stands for every digit key rather than just `code`.
+    pub digits: bool,
+}
+
+impl Key {
+    const fn plain(code: KeyCode) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            digits: false,
+        }
+    }
+
+    const fn digits() -> Self {
+        Self {
+            digits: true,
+            ..Self::char('0')
+        }
+    }
+
+    const fn char(c: char) -> Self {
+        Self::plain(KeyCode::Char(c))
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Self::ctrl_code(KeyCode::Char(c))
+    }
+
+    const fn ctrl_code(code: KeyCode) -> Self {
+        Self {
+            code,
+            ctrl: true,
+            digits: false,
+        }
+    }
+
+    /// Shift is ignored: it is already part of an upper-case `Char`.
+    fn matches(&self, event: &KeyEvent) -> bool {
+        let code = if self.digits {
+            matches!(event.code, KeyCode::Char('0'..='9'))
+        } else {
+            self.code == event.code
+        };
+        code && self.ctrl == event.modifiers.contains(KeyModifiers::CONTROL)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("^")?;
+        }
+        if self.digits {
+            return f.write_str("0–9");
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+pub struct KeyBinding {
+    pub key: Key,
+    pub description: &'static str,
+    pub action: Action,
+    /// Footer label; consecutive bindings sharing one are shown together
+    /// (`b/s trade`). Unhinted bindings only appear in the `?` help.
+    pub hint: Option<&'static str>,
+}
+
+const fn bind(
+    key: Key,
+    action: Action,
+    hint: Option<&'static str>,
+    description: &'static str,
+) -> KeyBinding {
+    KeyBinding {
+        key,
+        description,
+        action,
+        hint,
+    }
+}
+
+/// Every binding, in the order the footer and help list them.
+pub const BINDINGS: &[KeyBinding] = &[
+    bind(
+        Key::char('q'),
+        Action::Quit,
+        Some("quit"),
+        "Quit (asks first with --confirm-quit)",
+    ),
+    bind(
+        Key::plain(KeyCode::Up),
+        Action::SelectPrev,
+        Some("select"),
+        "Select the previous stock",
+    ),
+    bind(
+        Key::plain(KeyCode::Down),
+        Action::SelectNext,
+        Some("select"),
+        "Select the next stock",
+    ),
+    bind(
+        Key::digits(),
+        Action::JumpDigit,
+        Some("jump"),
+        "Jump to that stock number; digits typed quickly make one number",
+    ),
+    bind(
+        Key::plain(KeyCode::Enter),
+        Action::ToggleDetail,
+        Some("detail"),
+        "Toggle detail view of the selected stock",
+    ),
+    bind(
+        Key::char('m'),
+        Action::ToggleMultiTimeframe,
+        Some("multi-timeframe"),
+        "Toggle multi-timeframe in the detail view",
+    ),
+    bind(
+        Key::char('f'),
+        Action::ToggleFade,
+        Some("fade"),
+        "Toggle fading of the oldest chart points",
+    ),
+    bind(
+        Key::char('z'),
+        Action::CycleZoom,
+        Some("zoom"),
+        "Cycle fullscreen chart: backend → frontend → split",
+    ),
+    bind(
+        Key::char('o'),
+        Action::ToggleOverlay,
+        Some("overlay"),
+        "Overlay the selected stock's frontend on the backend chart",
+    ),
+    bind(
+        Key::char('R'),
+        Action::ToggleReturns,
+        Some("returns"),
+        "Plot per-tick returns instead of prices",
+    ),
+    bind(
+        Key::char('N'),
+        Action::ToggleLogReturns,
+        None,
+        "Use log returns ln(p1/p0) instead of simple ones everywhere",
+    ),
+    bind(
+        Key::char('x'),
+        Action::ToggleAbsoluteX,
+        Some("ticks"),
+        "Label the x-axis with absolute tick numbers",
+    ),
+    bind(
+        Key::ctrl('h'),
+        Action::SplitHorizontal,
+        Some("split"),
+        "Split the focused pane side by side",
+    ),
+    bind(
+        Key::ctrl('v'),
+        Action::SplitVertical,
+        Some("split"),
+        "Split the focused pane stacked",
+    ),
+    bind(
+        Key::char('b'),
+        Action::Buy,
+        Some("trade"),
+        "Paper-buy one unit of the selected stock",
+    ),
+    bind(
+        Key::char('s'),
+        Action::Sell,
+        Some("trade"),
+        "Paper-sell one unit of the selected stock",
+    ),
+    bind(
+        Key::char('B'),
+        Action::BuySized,
+        Some("trade"),
+        "Paper-buy the suggested volatility-based size",
+    ),
+    bind(
+        Key::char('S'),
+        Action::SellSized,
+        Some("trade"),
+        "Paper-sell the suggested volatility-based size",
+    ),
+    bind(
+        Key::char('e'),
+        Action::Export,
+        Some("export"),
+        "Export the selected stock's visible window to CSV",
+    ),
+    bind(
+        Key::char('l'),
+        Action::ToggleSmoothLatency,
+        Some("lat"),
+        "Show latest or smoothed tick latency",
+    ),
+    bind(
+        Key::char('r'),
+        Action::ResetLatency,
+        Some("lat"),
+        "Reset the min/avg/max tick latency",
+    ),
+    bind(
+        Key::char('u'),
+        Action::CycleTimeUnit,
+        Some("unit"),
+        "Cycle the unit of latencies and ages",
+    ),
+    bind(
+        Key::char('i'),
+        Action::CycleIndicatorPage,
+        Some("page"),
+        "Cycle the backend pointer columns",
+    ),
+    bind(
+        Key::char('n'),
+        Action::NewsShock,
+        Some("news"),
+        "Inject a news shock on a random subset of stocks",
+    ),
+    bind(
+        Key::char('<'),
+        Action::VolDown,
+        Some("vol"),
+        "Lower the volatility multiplier",
+    ),
+    bind(
+        Key::char('>'),
+        Action::VolUp,
+        Some("vol"),
+        "Raise the volatility multiplier",
+    ),
+    bind(
+        Key::char(' '),
+        Action::TogglePause,
+        Some("pause"),
+        "Pause / resume the simulation",
+    ),
+    bind(
+        Key::char('?'),
+        Action::ToggleHelp,
+        Some("help"),
+        "Show / hide this help",
+    ),
+    bind(
+        Key::plain(KeyCode::Left),
+        Action::CrosshairLeft,
+        None,
+        "Move the crosshair back a tick",
+    ),
+    bind(
+        Key::plain(KeyCode::Right),
+        Action::CrosshairRight,
+        None,
+        "Move the crosshair forward (past the newest hides it)",
+    ),
+    bind(
+        Key::ctrl_code(KeyCode::Left),
+        Action::PlaybackBack,
+        None,
+        "Draw a loaded replay as it stood further back (playback cursor)",
+    ),
+    bind(
+        Key::ctrl_code(KeyCode::Right),
+        Action::PlaybackForward,
+        None,
+        "Move the playback cursor forward (past the end goes live)",
+    ),
+    bind(
+        Key::char(':'),
+        Action::OpenCommand,
+        None,
+        "Command line: alert PRICE, unalert PRICE, alert clear (selected stock)",
+    ),
+    bind(
+        Key::char('I'),
+        Action::ToggleInspect,
+        None,
+        "Inspect the selected stock's tick under the crosshair, unrounded",
+    ),
+    bind(
+        Key::plain(KeyCode::Esc),
+        Action::ResumeLive,
+        None,
+        "Back to the live charts from inspecting or the playback cursor",
+    ),
+    bind(
+        Key::char('L'),
+        Action::ToggleLogScale,
+        None,
+        "Plot prices on a logarithmic y-axis",
+    ),
+    bind(
+        Key::ctrl('w'),
+        Action::ClosePane,
+        None,
+        "Close the focused pane",
+    ),
+    bind(
+        Key::plain(KeyCode::Tab),
+        Action::FocusNextPane,
+        None,
+        "Focus the next pane",
+    ),
+    bind(
+        Key::char('c'),
+        Action::CyclePaneContent,
+        None,
+        "Cycle the focused pane's content",
+    ),
+    bind(
+        Key::char('t'),
+        Action::ToggleTicks,
+        None,
+        "Show / hide the last raw ticks of each stock",
+    ),
+    bind(
+        Key::char('P'),
+        Action::Snapshot,
+        None,
+        "Snapshot every price; two or more show a diff",
+    ),
+    bind(
+        Key::char('C'),
+        Action::ToggleCorrelation,
+        None,
+        "Show / hide the return correlation matrix",
+    ),
+    bind(
+        Key::char('.'),
+        Action::Step,
+        None,
+        "While paused, advance every stock one tick",
+    ),
+    bind(
+        Key::char('['),
+        Action::ReplaySlower,
+        None,
+        "Halve replay speed",
+    ),
+    bind(
+        Key::char(']'),
+        Action::ReplayFaster,
+        None,
+        "Double replay speed",
+    ),
+    bind(
+        Key::char('y'),
+        Action::ToggleYLock,
+        None,
+        "Pin the overview charts' y-axes where they are / auto-scale again",
+    ),
+    bind(
+        Key::char('M'),
+        Action::CycleScenario,
+        None,
+        "Next market scenario: calm → trending → volatile → crash → flash crash",
+    ),
+    bind(
+        Key::char('T'),
+        Action::ToggleTicker,
+        None,
+        "Show / hide a scrolling ticker tape of every stock",
+    ),
+    bind(
+        Key::char('a'),
+        Action::AddStock,
+        None,
+        "Add a stock starting at 100",
+    ),
+    bind(
+        Key::char('d'),
+        Action::RemoveStock,
+        None,
+        "Remove the last stock (never the only one)",
+    ),
+    bind(
+        Key::char('g'),
+        Action::ToggleCandleGrid,
+        None,
+        "Show / hide candles of every stock in a grid",
+    ),
+    bind(
+        Key::plain(KeyCode::PageDown),
+        Action::NextCandlePage,
+        None,
+        "Next page of the candle grid",
+    ),
+    bind(
+        Key::plain(KeyCode::PageUp),
+        Action::PrevCandlePage,
+        None,
+        "Previous page of the candle grid",
+    ),
+    bind(
+        Key::char('{'),
+        Action::NarrowerCandles,
+        None,
+        "Fewer ticks per candle (1–120)",
+    ),
+    bind(
+        Key::char('}'),
+        Action::WiderCandles,
+        None,
+        "More ticks per candle (1–120)",
+    ),
+];
+
+pub fn action_for(event: &KeyEvent) -> Option<Action> {
+    BINDINGS
+        .iter()
+        .find(|b| b.key.matches(event))
+        .map(|b| b.action)
+}
+
+/// `q quit · ↑/↓ select · …` for the footer.
+pub fn footer_hints() -> String {
+    let mut groups: Vec<(String, &str)> = vec![];
+    for binding in BINDINGS {
+        let Some(hint) = binding.hint else { continue };
+        match groups.last_mut() {
+            Some((keys, last)) if *last == hint => keys.push_str(&format!("/{}", binding.key)),
+            _ => groups.push((binding.key.to_string(), hint)),
+        }
+    }
+    groups
+        .iter()
+        .map(|(keys, hint)| format!("{} {}", keys, hint))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_key_is_bound_twice() {
+        for (i, a) in BINDINGS.iter().enumerate() {
+            for b in &BINDINGS[i + 1..] {
+                assert_ne!(
+                    a.key, b.key,
+                    "{} bound to both {:?} and {:?}",
+                    a.key, a.action, b.action
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ctrl_bindings_need_ctrl() {
+        let plain = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        let ctrl = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL);
+        assert_eq!(action_for(&plain), None);
+        assert_eq!(action_for(&ctrl), Some(Action::SplitHorizontal));
+    }
+
+    #[test]
+    fn every_digit_jumps_and_nothing_else_claims_one() {
+        for c in '0'..='9' {
+            let event = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            let bound: Vec<Action> = BINDINGS
+                .iter()
+                .filter(|b| b.key.matches(&event))
+                .map(|b| b.action)
+                .collect();
+            assert_eq!(bound, [Action::JumpDigit]);
+        }
+    }
+}
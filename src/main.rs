@@ -1,36 +1,246 @@
-use std::fs::{self, OpenOptions};
-use std::io::{self, stdout, Write};
+mod alerts;
+mod app;
+mod chart;
+mod checkpoint;
+mod clock;
+mod command;
+mod config;
+mod export;
+mod frontend;
+mod golden;
+mod indicators;
+mod instruments;
+#[cfg(unix)]
+mod ipc;
+mod keys;
+mod orders;
+mod panes;
+mod record;
+mod sim;
+mod summary;
+mod terminal;
+mod theme;
+mod ticklog;
+mod ui;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossterm::{
-    event::{self, Event, KeyCode},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand,
-};
+use clap::Parser;
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use log::{info, error};
 use rand::Rng;
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
-    symbols,
-    widgets::{Axis, Block, Borders, Chart, Dataset, Paragraph},
-    Terminal,
-};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Direction;
 use redis::AsyncCommands;
 use sqlx::postgres::PgPoolOptions;
 
-const HISTORY_LEN: usize = 50;
+use crate::alerts::{AlertLog, Notifier, PriceLevels};
+use crate::clock::Zone;
+use crate::command::Command;
+use crate::app::{App, Mode};
+use crate::config::Config;
+use crate::export::{IndicatorRow, SessionStats};
+use crate::instruments::Instrument;
+use crate::keys::Action;
+use crate::orders::{Blotter, Side, ORDER_QTY};
+use crate::sim::{
+    Band, Book, ClosedTicks, Fault, FaultInjector, Ohlc, Recording, Replay, Scenario, Session,
+    SimControl, TickGrid, ZeroPolicy,
+};
+use crate::terminal::TerminalGuard;
+
 const MOVING_AVG_LEN: usize = 5;
+/// Consecutive input errors tolerated before the main loop gives up.
+const MAX_INPUT_ERRORS: u32 = 5;
+/// Target period of the frontend moving-average refresh.
+const FRONTEND_INTERVAL: Duration = Duration::from_millis(300);
+/// Longest `--redraw-on-change` goes without drawing, so clocks and ages
+/// still move while the data is idle.
+const MAX_IDLE_REDRAW: Duration = Duration::from_secs(1);
+/// Pause between `--accessible` summaries; slow enough to be read out.
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(10);
+/// How often a paused updater checks for a step or resume.
+const PAUSE_POLL: Duration = Duration::from_millis(10);
 
 #[derive(Clone)]
 struct MarketData {
     count: usize,
+    symbol: String,
+    name: String,
+    /// Half-width of the uniform per-tick price shock.
+    sigma: f64,
+    /// Where the stock started, for `--zero-policy reseed`.
+    initial_price: f64,
+    /// Out of the charts and no longer ticking (`--zero-policy hide`).
+    hidden: bool,
     price: Arc<RwLock<f64>>,
     last_update: Instant,
     history: Vec<f64>,
+    /// When each `history` sample was taken, index for index.
+    times: Vec<Instant>,
+    /// Units traded on each `history` sample's tick, index for index; 0 on
+    /// seed padding and outside the session.
+    volumes: Vec<u64>,
+    /// `--replay-ohlc` bars behind the newest samples, oldest first; the
+    /// candle view draws these instead of aggregating ticks.
+    bars: Vec<Ohlc>,
+    allow_negative: bool,
+    tick_interval: Duration,
+    next_update: Instant,
+    ticks: u64,
+    /// Consecutive in-session ticks that left the price exactly as it was.
+    unchanged: u64,
+    /// How late each tick fired after its deadline.
+    latency: LatencyStats,
+    /// Integer price behind `price` when `--tick-size` is set.
+    grid: Option<TickGrid>,
+    /// `--price-bands` range random-walk ticks are reflected into.
+    band: Option<Band>,
+    book: Book,
+    closed: ClosedTicks,
+    stats: SessionStats,
+    /// `:alert` levels, checked against every tick.
+    levels: PriceLevels,
+    /// Price the `flash-crash-recovery` scenario pulls the stock back to.
+    anchor: Option<f64>,
+}
+
+/// How a stock's history is trimmed after each tick.
+#[derive(Clone, Copy)]
+enum Eviction {
+    /// Keep the newest N samples (`--history`).
+    Count(usize),
+    /// Keep the samples younger than this (`--history-seconds`), however
+    /// many there are.
+    Age(Duration),
+}
+
+impl Eviction {
+    /// How many of the oldest samples, stamped `times`, to drop once a
+    /// sample stamped `at` has been pushed.
+    fn stale(self, times: &[Instant], at: Instant) -> usize {
+        match self {
+            Eviction::Count(n) => times.len().saturating_sub(n),
+            Eviction::Age(window) => times
+                .iter()
+                .take_while(|t| at.saturating_duration_since(**t) > window)
+                .count(),
+        }
+    }
+}
+
+impl MarketData {
+    /// Stock number `count` at its start price, with a flat seed history.
+    fn new(count: usize, inst: &Instrument, config: &Config) -> Self {
+        let grid = config
+            .tick_size()
+            .map(|size| TickGrid::new(size, inst.initial_price));
+        let init = grid.map_or(inst.initial_price, |g| g.price());
+        MarketData {
+            count,
+            symbol: inst.symbol.clone(),
+            name: inst.name.clone(),
+            sigma: inst.sigma,
+            initial_price: init,
+            hidden: false,
+            price: Arc::new(RwLock::new(init)),
+            last_update: Instant::now(),
+            history: vec![init; config.history],
+            times: vec![Instant::now(); config.history],
+            volumes: vec![0; config.history],
+            bars: vec![],
+            allow_negative: config.allows_negative(count),
+            tick_interval: config.tick_interval(count),
+            next_update: Instant::now(),
+            ticks: 0,
+            unchanged: 0,
+            latency: LatencyStats::default(),
+            grid,
+            band: config.price_band(count),
+            book: Book::default(),
+            closed: ClosedTicks::default(),
+            stats: SessionStats::default(),
+            levels: PriceLevels::default(),
+            anchor: None,
+        }
+    }
+
+    /// `price` as the next history sample, or the last sample again when it
+    /// moved no further than `floor` (`--noise-floor`). Repeating instead of
+    /// skipping keeps one sample per tick, which the x-axes rely on.
+    fn debounced(&self, price: f64, floor: f64) -> f64 {
+        match self.history.last() {
+            Some(&last) if (price - last).abs() <= floor && floor > 0.0 => last,
+            _ => price,
+        }
+    }
+
+    fn push_sample(&mut self, price: f64, volume: u64, at: Instant, eviction: Eviction) {
+        self.history.push(price);
+        self.times.push(at);
+        self.volumes.push(volume);
+        let stale = eviction.stale(&self.times, at);
+        self.history.drain(..stale);
+        self.times.drain(..stale);
+        self.volumes.drain(..stale);
+        self.bars.drain(..self.bars.len().saturating_sub(self.history.len()));
+    }
+
+    /// `--frozen-ticks`: the price has not changed for `after` ticks in a
+    /// row. The alert and the FROZEN tag both go by this.
+    fn frozen(&self, after: u64) -> bool {
+        self.unchanged >= after
+    }
+
+    /// The part of `history` actually produced by ticks (plus the starting
+    /// price); the rest is seed padding so the charts start full.
+    fn samples(&self) -> &[f64] {
+        let real = (self.ticks as usize).saturating_add(1);
+        &self.history[self.history.len().saturating_sub(real)..]
+    }
+}
+
+/// Session min / mean / max of a duration, reset with `r`.
+#[derive(Clone, Default)]
+struct LatencyStats {
+    min: Option<Duration>,
+    max: Duration,
+    total: Duration,
+    samples: u32,
+    /// Most recent sample.
+    last: Duration,
+    /// EWMA of the samples, for a display that does not flicker.
+    smoothed: Duration,
+}
+
+impl LatencyStats {
+    fn record(&mut self, d: Duration) {
+        self.smoothed = if self.samples == 0 {
+            d
+        } else {
+            d.mul_f64(indicators::EWMA_ALPHA)
+                + self.smoothed.mul_f64(1.0 - indicators::EWMA_ALPHA)
+        };
+        self.last = d;
+        self.min = Some(self.min.map_or(d, |m| m.min(d)));
+        self.max = self.max.max(d);
+        self.total += d;
+        self.samples += 1;
+    }
+
+    fn mean(&self) -> Duration {
+        if self.samples == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.samples
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -38,20 +248,70 @@ struct UiData {
     count: usize,
     value: Arc<f64>,
     last_update: Instant,
+    /// Smoothed observed time between refreshes (the target is only a sleep).
+    interval: Duration,
     history: Vec<f64>,
+    /// When each `history` sample was taken, for `Eviction::Age`.
+    times: Vec<Instant>,
 }
 
-// -------------------- Helper functions --------------------
+impl UiData {
+    fn new(count: usize, inst: &Instrument) -> Self {
+        UiData {
+            count,
+            value: Arc::new(inst.initial_price),
+            last_update: Instant::now(),
+            interval: FRONTEND_INTERVAL,
+            history: vec![],
+            times: vec![],
+        }
+    }
 
-fn append_to_file(stock_id: i32, price: f64) -> std::io::Result<()> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("stock_data.txt")?;
-    writeln!(file, "{},{}", stock_id, price)?;
-    Ok(())
+    fn push_sample(&mut self, value: f64, at: Instant, eviction: Eviction) {
+        self.history.push(value);
+        self.times.push(at);
+        let stale = eviction.stale(&self.times, at);
+        self.history.drain(..stale);
+        self.times.drain(..stale);
+    }
+}
+
+/// `a`: appends a default stock (numbered like the built-in ones) to both
+/// vectors. The market data lock is taken first, in the same order the
+/// frontend updater takes them.
+fn add_stock(
+    market_data: &RwLock<Vec<MarketData>>,
+    ui_data: &RwLock<Vec<UiData>>,
+    config: &Config,
+) -> usize {
+    let mut md_vec = market_data.write().unwrap();
+    let mut ui_vec = ui_data.write().unwrap();
+    let count = md_vec.len();
+    let inst = instruments::numbered(count);
+    md_vec.push(MarketData::new(count, &inst, config));
+    ui_vec.push(UiData::new(count, &inst));
+    count + 1
 }
 
+/// `d`: drops the last stock, unless it is the only one, along with its
+/// orders. Returns the remaining count.
+fn remove_stock(
+    market_data: &RwLock<Vec<MarketData>>,
+    ui_data: &RwLock<Vec<UiData>>,
+    blotter: &RwLock<Blotter>,
+) -> usize {
+    let mut md_vec = market_data.write().unwrap();
+    let mut ui_vec = ui_data.write().unwrap();
+    if md_vec.len() > 1 {
+        md_vec.pop();
+        ui_vec.pop();
+        blotter.write().unwrap().forget_stock(md_vec.len());
+    }
+    md_vec.len()
+}
+
+// -------------------- Helper functions --------------------
+
 async fn flush_file_to_postgres(pool: Arc<sqlx::PgPool>) -> std::io::Result<()> {
     let content = fs::read_to_string("stock_data.txt")?;
     if content.is_empty() {
@@ -90,6 +350,50 @@ async fn flush_file_to_postgres(pool: Arc<sqlx::PgPool>) -> std::io::Result<()>
     Ok(())
 }
 
+/// Waits up to `timeout` for input, then drains whatever else is queued.
+/// Repeats of a key within one batch are dropped, so a held key applies at
+/// most once per frame instead of piling up, unless `typing` (where "100"
+/// has to keep both zeros) or the key is a digit jump (where "11" does).
+fn poll_keys(timeout: Duration, typing: bool) -> io::Result<Vec<KeyEvent>> {
+    let mut keys: Vec<KeyEvent> = vec![];
+    let mut wait = timeout;
+    while event::poll(wait)? {
+        if let Event::Key(key) = event::read()? {
+            let digit = keys::action_for(&key) == Some(Action::JumpDigit);
+            if typing || digit || !keys.iter().any(|k| k.code == key.code) {
+                keys.push(key);
+            }
+        }
+        wait = Duration::ZERO;
+    }
+    Ok(keys)
+}
+
+/// `--accessible`: instead of the TUI, print one prose line per stock every
+/// `SUMMARY_INTERVAL` for a screen reader. Runs until interrupted.
+fn run_accessible(
+    market_data: &RwLock<Vec<MarketData>>,
+    lookback: usize,
+    deadline: Option<Instant>,
+) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    loop {
+        let left = deadline.map_or(SUMMARY_INTERVAL, |d| {
+            d.saturating_duration_since(Instant::now())
+        });
+        if left < SUMMARY_INTERVAL {
+            thread::sleep(left);
+            return Ok(());
+        }
+        thread::sleep(SUMMARY_INTERVAL);
+        let md_vec = market_data.read().unwrap().clone();
+        for md in &md_vec {
+            writeln!(stdout, "{}", summary::summarize(md, lookback))?;
+        }
+        writeln!(stdout)?;
+    }
+}
+
 fn init_logging() {
     env_logger::Builder::from_default_env()
         .target(env_logger::Target::Stdout)
@@ -100,10 +404,46 @@ fn init_logging() {
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> io::Result<()> {
+    let start = Instant::now();
     init_logging();
+    let config = Config::parse();
+    if let Some(path) = &config.play {
+        return record::play(path);
+    }
 
-    let n_stocks = 3;
-    let colors = [Color::Red, Color::Green, Color::Yellow];
+    let instruments = match &config.instruments {
+        Some(path) => instruments::load(path)?,
+        None => instruments::defaults(),
+    };
+    if let Some(steps) = config.golden {
+        let seed = config.seed.unwrap_or(0);
+        return golden::run(&config, &instruments, seed, steps, &mut io::stdout().lock());
+    }
+    // Checked before connecting to anything, so a piped run fails at once
+    if !config.accessible {
+        if let Some(reason) = terminal::unsupported() {
+            eprintln!(
+                "rust_hft_tui: {}. Run it in a terminal, or use --accessible for \
+                 plain-text summaries or --golden STEPS for an indicator table.",
+                reason
+            );
+            std::process::exit(1);
+        }
+    }
+    let n_stocks = instruments.len();
+    let history_len = config.history;
+    let eviction = match config.history_seconds {
+        Some(secs) => Eviction::Age(Duration::from_secs(secs)),
+        None => Eviction::Count(history_len),
+    };
+    let noise_floor = config.noise_floor;
+    let zero_policy = config.zero_policy;
+    let batch = config.update_batch.map_or(usize::MAX, |n| n as usize);
+    let frozen_ticks = config.frozen_ticks;
+    // A fault's frozen run is long enough for the FROZEN guard to see it
+    let mut faults = config
+        .inject_faults
+        .map(|rate| FaultInjector::new(rate, frozen_ticks + frozen_ticks / 2));
 
     // --- Postgres pool ---
     let pg_pool = PgPoolOptions::new()
@@ -118,73 +458,336 @@ async fn main() -> io::Result<()> {
     let redis_client = Arc::new(redis_client);
 
     // --- Market data ---
-    let market_data = Arc::new(RwLock::new(
-        (0..n_stocks)
-            .map(|i| {
-                let init = 100.0;
-                MarketData {
-                    count: i,
-                    price: Arc::new(RwLock::new(init)),
-                    last_update: Instant::now(),
-                    history: vec![init; HISTORY_LEN],
-                }
-            })
-            .collect::<Vec<_>>(),
-    ));
+    let mut md_vec: Vec<MarketData> = instruments
+        .iter()
+        .enumerate()
+        .map(|(i, inst)| MarketData::new(i, inst, &config))
+        .collect();
+    if let Some(path) = &config.load_state {
+        let restored = checkpoint::restore(&mut md_vec, checkpoint::load(path)?, history_len);
+        info!("Restored {} stocks from {}", restored, path.display());
+    }
+    let market_data = Arc::new(RwLock::new(md_vec));
 
     // --- UI data ---
     let ui_data = Arc::new(RwLock::new(
-        (0..n_stocks)
-            .map(|i| UiData {
-                count: i,
-                value: Arc::new(100.0),
-                last_update: Instant::now(),
-                history: vec![],
-            })
+        instruments
+            .iter()
+            .enumerate()
+            .map(|(i, inst)| UiData::new(i, inst))
             .collect::<Vec<_>>(),
     ));
 
+    // --- Simulation control ---
+    let alerts = Arc::new(RwLock::new(AlertLog::default()));
+    let mut replay = match &config.replay {
+        Some(path) => Some(Replay::load(path, n_stocks)?),
+        None if !config.replay_ohlc.is_empty() => {
+            Some(Replay::load_ohlc(&config.replay_ohlc, n_stocks)?)
+        }
+        None if config.stdin => {
+            let symbols = instruments.iter().map(|i| i.symbol.clone()).collect();
+            Some(Replay::stdin(symbols, Arc::clone(&alerts)))
+        }
+        None => None,
+    };
+    let recording = replay.as_ref().and_then(Replay::recording);
+    let (rng, seed, fixed_seed) = sim::new_rng(config.seed);
+    info!("Simulating with seed {} (--seed {} repeats this run)", seed, seed);
+    let mut control = SimControl::new(replay.is_some());
+    control.seed = seed;
+    control.fixed_seed = fixed_seed;
+    control.session = config
+        .session
+        .map(|hours| Session::new(hours, config.tick_minutes));
+    control.regime.jumps = config.jumps();
+    if let Some(scenario) = config.scenario {
+        let mut md_vec = market_data.write().unwrap();
+        let message = sim::apply_scenario(&mut control, &mut md_vec, scenario);
+        alerts.write().unwrap().push(message);
+    }
+    let sim_control = Arc::new(RwLock::new(control));
+    if fixed_seed {
+        alerts
+            .write()
+            .unwrap()
+            .push("No OS entropy: simulating with a fixed seed".to_string());
+    } else if config.seed.is_none() {
+        alerts
+            .write()
+            .unwrap()
+            .push(format!("Seed {}: --seed {} repeats this run", seed, seed));
+    }
+    let blotter = Arc::new(RwLock::new(Blotter::default()));
+    let fill_latency = Duration::from_millis(config.fill_latency);
+    let limits = orders::Limits {
+        max_position: config.max_position,
+        max_notional: config.max_notional,
+    };
+    let indicator_export = match &config.export_indicators {
+        Some(path) => Some(export::spawn_indicator_writer(
+            path,
+            config.tz.unwrap_or_else(Zone::local),
+        )?),
+        None => None,
+    };
+    let (tick_log, tick_log_writer) =
+        ticklog::spawn(PathBuf::from("stock_data.txt"), config.tick_flush);
+    // Bumped by the updater threads whenever there is something new to draw
+    let data_version = Arc::new(AtomicU64::new(0));
+    // Bumped by the frontend updater alone, on every refresh (each one adds
+    // to the frontend histories), so its chart is rebuilt only then
+    let frontend_version = Arc::new(AtomicU64::new(0));
+
     // --- Backend updater thread ---
     {
         let md_clone = Arc::clone(&market_data);
+        let control = Arc::clone(&sim_control);
+        let alerts = Arc::clone(&alerts);
+        let blotter = Arc::clone(&blotter);
+        let version = Arc::clone(&data_version);
         let pg_pool = Arc::clone(&pg_pool);
         let redis_client = Arc::clone(&redis_client);
 
         thread::spawn(move || {
-            let mut rng = rand::thread_rng();
+            let mut rng = rng;
             let rt = tokio::runtime::Runtime::new().unwrap();
             let flush_interval = Duration::from_secs(1);
             let mut last_flush = Instant::now();
+            // Deadlines go stale while paused, so the first pass after resuming
+            // is not counted as latency
+            let mut was_paused = false;
 
             loop {
-                {
+                // Each stock ticks on its own deadline; sleep until the earliest one
+                let (speed, vol, regime, news, paused, step, open) = {
+                    let mut ctl = control.write().unwrap();
+                    let news = std::mem::take(&mut ctl.pending_news);
+                    let step = ctl.take_step();
+                    let open = ctl.session.is_none_or(|s| s.is_open());
+                    (ctl.replay_speed, ctl.vol, ctl.regime, news, ctl.paused, step, open)
+                };
+                let next_due = {
+                    let now = Instant::now();
                     let mut vec = md_clone.write().unwrap();
-                    for md in vec.iter_mut() {
-                        let delta = rng.gen_range(-2.0..2.0);
-                        let mut p = md.price.write().unwrap();
-                        *p += delta;
-                        md.last_update = Instant::now();
-                        md.history.push(*p);
-                        if md.history.len() > HISTORY_LEN {
-                            md.history.remove(0);
-                        }
-
-                        let stock_id = md.count as i32;
-                        let price_f64 = *p;
+                    let mut changed = news;
+                    if news {
+                        let message = sim::apply_news_shock(&mut vec, &mut rng);
+                        info!("{}", message);
+                        alerts.write().unwrap().push(message);
+                    }
+                    // While paused only a requested step ticks, and it ticks every stock
+                    let due = |md: &MarketData| {
+                        !md.hidden && if paused { step } else { md.next_update <= now }
+                    };
+                    let mut ticked = false;
+                    // Stocks looked at so far; with --update-batch the lock is let go
+                    // after each batch so the renderer is not kept waiting on all of them
+                    let mut done = 0;
+                    loop {
+                        for md in vec.iter_mut().skip(done).take(batch).filter(|md| due(md)) {
+                            ticked = true;
+                            if !paused && !was_paused {
+                                let late = Instant::now().saturating_duration_since(md.next_update);
+                                md.latency.record(late);
+                            }
+                            md.next_update = now + md.tick_interval.div_f64(speed);
+                            // Held through the tick while `md` itself is updated
+                            let price = Arc::clone(&md.price);
+                            let mut p = price.write().unwrap();
+                            let before = *p;
+                            let sigma = md.sigma * vol;
+                            let frozen = faults.as_mut().is_some_and(|f| f.freezing(md.count));
+                            let fault = match faults.as_mut() {
+                                Some(faults) if open && !frozen => {
+                                    faults.next(&mut rng, md.count, md.allow_negative)
+                                }
+                                _ => None,
+                            };
+                            if let Some(fault) = fault {
+                                let message = format!("Injected {} on {}", fault, md.symbol);
+                                info!("{}", message);
+                                alerts.write().unwrap().push(message);
+                            }
+                            if fault == Some(Fault::Dropped) {
+                                continue;
+                            }
+                            if !open {
+                                // Outside the session the price holds at the last close
+                            } else if frozen || fault == Some(Fault::Frozen) {
+                                // A stuck feed repeats its last price
+                            } else if let Some(replay) = replay.as_mut() {
+                                let next = match replay.next_bar(md.count) {
+                                    Some(bar) => {
+                                        let close = bar.candle.close;
+                                        md.bars.push(bar);
+                                        Some(close)
+                                    }
+                                    None => replay.next_price(md.count),
+                                };
+                                match next {
+                                    Some(price) => sim::set_price(&mut p, &mut md.grid, price),
+                                    None => continue,
+                                }
+                            } else {
+                                let mut delta =
+                                    rng.gen_range(-sigma..=sigma) + regime.drift * sigma;
+                                if let Some(anchor) = md.anchor {
+                                    delta += regime.revert * (anchor - before);
+                                }
+                                let dt = md.tick_interval.div_f64(speed);
+                                let jump =
+                                    regime.jumps.and_then(|j| j.sample(&mut rng, before, dt));
+                                if let Some(jump) = jump.filter(|_| before != 0.0) {
+                                    let pct = jump / before.abs() * 100.0;
+                                    let message = format!("{} gapped {:+.2}%", md.symbol, pct);
+                                    info!("{}", message);
+                                    alerts.write().unwrap().push(message);
+                                    delta += jump;
+                                }
+                                sim::shift_price(
+                                    &mut p,
+                                    &mut md.grid,
+                                    delta,
+                                    md.allow_negative,
+                                    md.band,
+                                );
+                                if *p == 0.0 && !md.allow_negative {
+                                    let overshoot = (before + delta).abs();
+                                    let message = match zero_policy {
+                                        ZeroPolicy::Clamp => None,
+                                        ZeroPolicy::Reflect => {
+                                            sim::set_price(&mut p, &mut md.grid, overshoot);
+                                            None
+                                        }
+                                        ZeroPolicy::Reseed => {
+                                            sim::set_price(&mut p, &mut md.grid, md.initial_price);
+                                            Some(format!("{} hit zero, re-seeded", md.symbol))
+                                        }
+                                        ZeroPolicy::Hide => {
+                                            md.hidden = true;
+                                            Some(format!("{} hit zero, hidden", md.symbol))
+                                        }
+                                    };
+                                    if let Some(message) = message {
+                                        info!("{}", message);
+                                        alerts.write().unwrap().push(message);
+                                    }
+                                }
+                            }
+                            match fault {
+                                Some(Fault::Nan) => *p = f64::NAN,
+                                Some(Fault::Negative) => {
+                                    let negative = -(before.abs() + md.sigma);
+                                    sim::set_price(&mut p, &mut md.grid, negative);
+                                }
+                                _ => {}
+                            }
+                            // A bad print is thrown away and the last good price kept
+                            if let Some(what) = sim::bad_print(*p, md.allow_negative) {
+                                let message = format!(
+                                    "{}: {} price rejected, holding {:.2}",
+                                    md.symbol, what, before
+                                );
+                                info!("{}", message);
+                                alerts.write().unwrap().push(message);
+                                sim::set_price(&mut p, &mut md.grid, before);
+                            }
+                            let tilt = if sigma > 0.0 { (*p - before) / sigma } else { 0.0 };
+                            md.book = Book::after_tick(&mut rng, tilt.clamp(-1.0, 1.0));
+                            let volume = if open {
+                                sim::tick_volume(&mut rng, tilt.clamp(-1.0, 1.0))
+                            } else {
+                                0
+                            };
+                            md.last_update = Instant::now();
+                            md.ticks += 1;
+                            if open {
+                                let was_frozen = md.frozen(frozen_ticks);
+                                md.unchanged = if *p == before { md.unchanged + 1 } else { 0 };
+                                if md.frozen(frozen_ticks) && !was_frozen {
+                                    let message = format!(
+                                        "{} frozen: {} ticks unchanged",
+                                        md.symbol, frozen_ticks
+                                    );
+                                    info!("{}", message);
+                                    alerts.write().unwrap().push(message);
+                                }
+                            }
+                            changed = true;
+                            let sample = md.debounced(*p, noise_floor);
+                            md.push_sample(sample, volume, md.last_update, eviction);
+                            if !open {
+                                md.closed.mark(md.ticks, md.history.len());
+                                continue;
+                            }
+                            md.stats.record(*p);
+                            for (level, rising) in md.levels.crossed(before, *p) {
+                                let way = if rising { "up" } else { "down" };
+                                let message = format!("{} crossed {:.2} {}", md.symbol, level, way);
+                                info!("{}", message);
+                                alerts.write().unwrap().push(message);
+                            }
+                            if let Some(tx) = &indicator_export {
+                                let _ = tx.send(IndicatorRow::new(md, *p, start));
+                            }
 
-                        let _ = append_to_file(stock_id, price_f64);
+                            let stock_id = md.count as i32;
+                            let price_f64 = *p;
 
-                        let redis_client = Arc::clone(&redis_client);
-                        rt.spawn(async move {
-                            if let Ok(mut conn) = redis_client.get_async_connection().await {
-                                let _: () = conn
-                                    .set(format!("stock:{}", stock_id), price_f64 as f32)
-                                    .await
-                                    .unwrap_or(());
+                            // Replayed ticks are already recorded
+                            if replay.as_ref().is_none_or(Replay::is_live) {
+                                tick_log.log(stock_id, price_f64);
                             }
-                        });
+
+                            let redis_client = Arc::clone(&redis_client);
+                            rt.spawn(async move {
+                                if let Ok(mut conn) = redis_client.get_async_connection().await {
+                                    let _: () = conn
+                                        .set(format!("stock:{}", stock_id), price_f64 as f32)
+                                        .await
+                                        .unwrap_or(());
+                                }
+                            });
+                        }
+                        done = done.saturating_add(batch);
+                        if done >= vec.len() {
+                            break;
+                        }
+                        drop(vec);
+                        vec = md_clone.write().unwrap();
                     }
-                }
+
+                    if ticked {
+                        if let Some(session) = control.write().unwrap().session.as_mut() {
+                            session.advance();
+                        }
+                    }
+
+                    let mut orders = blotter.write().unwrap();
+                    let fills = orders.fill_due(now, &vec);
+                    changed |= !fills.is_empty();
+                    for fill in fills {
+                        info!(
+                            "Filled {} {} {} @ {:.2} (slippage {:+.2})",
+                            fill.side,
+                            fill.qty,
+                            vec[fill.stock].symbol,
+                            fill.price,
+                            fill.slippage()
+                        );
+                    }
+                    if changed {
+                        version.fetch_add(1, Ordering::Relaxed);
+                    }
+                    was_paused = paused;
+                    let next_tick = if paused {
+                        now + PAUSE_POLL
+                    } else {
+                        vec.iter().map(|md| md.next_update).min().unwrap_or(now)
+                    };
+                    orders.next_fill().map_or(next_tick, |t| t.min(next_tick))
+                };
 
                 // Flush to Postgres every second
                 if last_flush.elapsed() >= flush_interval {
@@ -195,181 +798,369 @@ async fn main() -> io::Result<()> {
                     last_flush = Instant::now();
                 }
 
-                thread::sleep(Duration::from_millis(100));
+                thread::sleep(next_due.saturating_duration_since(Instant::now()));
             }
         });
     }
 
-    // --- Frontend updater thread (moving average) ---
+    // --- Frontend updater thread (lagged view of the market) ---
     {
         let md_clone = Arc::clone(&market_data);
         let ui_clone = Arc::clone(&ui_data);
+        let version = Arc::clone(&data_version);
+        let frontend_version = Arc::clone(&frontend_version);
+        let model = config.frontend_model;
+        let lag = config.frontend_lag as usize;
 
         thread::spawn(move || {
-            loop {
+            for refresh in 0.. {
                 {
                     let md_vec = md_clone.read().unwrap();
                     let mut ui_vec = ui_clone.write().unwrap();
                     for (i, ui) in ui_vec.iter_mut().enumerate() {
-                        let len = md_vec[i].history.len();
-                        let start = len.saturating_sub(MOVING_AVG_LEN);
-                        let slice = &md_vec[i].history[start..];
-                        let avg = slice.iter().sum::<f64>() / slice.len() as f64;
+                        let avg = model.next(&md_vec[i].history, *ui.value, refresh, lag);
+                        if avg != *ui.value {
+                            version.fetch_add(1, Ordering::Relaxed);
+                        }
 
                         let new_ptr = Arc::new(avg);
                         ui.value = new_ptr.clone();
+                        let observed = ui.last_update.elapsed();
+                        ui.interval = observed.mul_f64(indicators::EWMA_ALPHA)
+                            + ui.interval.mul_f64(1.0 - indicators::EWMA_ALPHA);
                         ui.last_update = Instant::now();
-                        ui.history.push(avg);
-                        if ui.history.len() > HISTORY_LEN {
-                            ui.history.remove(0);
-                        }
+                        ui.push_sample(avg, ui.last_update, eviction);
                     }
+                    frontend_version.fetch_add(1, Ordering::Relaxed);
                 }
-                thread::sleep(Duration::from_millis(300));
+                thread::sleep(FRONTEND_INTERVAL);
             }
         });
     }
 
+    if let Some(path) = &config.checkpoint {
+        checkpoint::spawn_writer(
+            path.clone(),
+            config.checkpoint_interval,
+            Arc::clone(&market_data),
+        );
+    }
+
+    // --- Snapshot socket ---
+    #[cfg(unix)]
+    if let Some(path) = &config.ipc_socket {
+        ipc::spawn_server(path, Arc::clone(&market_data))?;
+    }
+
+    if config.accessible {
+        run_accessible(
+            &market_data,
+            config.change_lookback,
+            config.duration.and_then(|d| start.checked_add(d)),
+        )?;
+        tick_log_writer.close();
+        let md_vec = market_data.read().unwrap().clone();
+        return write_summary(&config, &md_vec);
+    }
+
     // --- Terminal setup ---
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    stdout.execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let mut recorder = match &config.record {
+        Some(path) => Some(record::Recorder::create(path)?),
+        None => None,
+    };
+    let mut terminal = TerminalGuard::new(CrosstermBackend::new(io::stdout()))?;
 
     // --- Main loop ---
-    loop {
-        if event::poll(Duration::from_millis(10))? {
-            if let Event::Key(key) = event::read()? {
-                if key.code == KeyCode::Char('q') {
+    let mut app = App::new(&config, n_stocks, start);
+    app.playback_len = recording.as_ref().map_or(0, Recording::len);
+    for name in &config.indicators {
+        match name.as_str() {
+            "stddev" => app.register_indicator("σ", indicators::stddev),
+            "drawdown" => app.register_indicator("mdd", indicators::max_drawdown),
+            "median" => app.register_indicator("median", indicators::rolling_median),
+            "skew" => app.register_indicator("skew", |h| {
+                indicators::skewness(&indicators::returns(h))
+            }),
+            "kurt" => app.register_indicator("kurt", |h| {
+                indicators::kurtosis(&indicators::returns(h))
+            }),
+            _ => unreachable!("rejected by clap"),
+        }
+    }
+    let mut input_errors = 0;
+    let mut drawn_version = None;
+    let mut last_draw = Instant::now();
+    let mut frame_start = Instant::now();
+    let mut notifier = Notifier::default();
+    // What the charts show while held for inspection
+    let mut held: Option<(Instant, Vec<MarketData>, Vec<UiData>)> = None;
+    'main: loop {
+        // Wait for the next frame, but wake early on a key so input stays snappy
+        let mut pressed = false;
+        let wait = app.pacer.interval().saturating_sub(frame_start.elapsed());
+        match poll_keys(wait, app.mode == Mode::Command) {
+            Ok(keys) => {
+                input_errors = 0;
+                pressed = !keys.is_empty();
+                for key in keys {
+                    if app.mode == Mode::ConfirmingQuit {
+                        match key.code {
+                            KeyCode::Char('y') => break 'main,
+                            _ => app.mode = Mode::Normal,
+                        }
+                        continue;
+                    }
+                    if app.mode == Mode::Command {
+                        match key.code {
+                            KeyCode::Enter => {
+                                let line = app.close_command();
+                                let mut md_vec = market_data.write().unwrap();
+                                let md = &mut md_vec[app.selected];
+                                let notice = match command::parse(&line) {
+                                    Ok(Command::Alert(level)) => match md.levels.add(level) {
+                                        Ok(()) => format!(
+                                            "{}: alert at {:.2} ({} set)",
+                                            md.symbol,
+                                            level,
+                                            md.levels.len()
+                                        ),
+                                        Err(e) => e,
+                                    },
+                                    Ok(Command::Unalert(level)) if md.levels.remove(level) => {
+                                        format!("{}: alert at {:.2} removed", md.symbol, level)
+                                    }
+                                    Ok(Command::Unalert(level)) => {
+                                        format!("{}: no alert at {:.2}", md.symbol, level)
+                                    }
+                                    Ok(Command::ClearAlerts) => {
+                                        md.levels.clear();
+                                        format!("{}: alerts cleared", md.symbol)
+                                    }
+                                    Err(e) => e,
+                                };
+                                app.notify(notice);
+                            }
+                            KeyCode::Esc => {
+                                app.close_command();
+                            }
+                            KeyCode::Backspace => {
+                                app.command.pop();
+                            }
+                            KeyCode::Char(c) => app.command.push(c),
+                            _ => {}
+                        }
+                        continue;
+                    }
+                    let Some(action) = keys::action_for(&key) else {
+                        continue;
+                    };
+                    match action {
+                        Action::JumpDigit => {
+                            if let KeyCode::Char(c) = key.code {
+                                app.jump_digit(c as usize - '0' as usize);
+                            }
+                        }
+                        Action::SplitHorizontal => app.split_pane(Direction::Horizontal),
+                        Action::SplitVertical => app.split_pane(Direction::Vertical),
+                        Action::ClosePane => app.close_pane(),
+                        Action::FocusNextPane => app.focus_next_pane(),
+                        Action::CyclePaneContent => app.cycle_pane_content(),
+                        Action::Quit if app.confirm_quit => app.mode = Mode::ConfirmingQuit,
+                        Action::Quit => break 'main,
+                        Action::SelectNext => app.select_next(),
+                        Action::SelectPrev => app.select_prev(),
+                        Action::CrosshairLeft => app.crosshair_left(),
+                        Action::CrosshairRight => app.crosshair_right(),
+                        Action::ToggleDetail => app.toggle_detail(),
+                        Action::ToggleMultiTimeframe => app.toggle_multi_timeframe(),
+                        Action::ToggleFade => app.toggle_fade(),
+                        Action::CycleZoom => app.cycle_zoom(),
+                        Action::ToggleOverlay => app.toggle_overlay_frontend(),
+                        Action::ToggleReturns => app.toggle_returns(),
+                        Action::ToggleLogReturns => app.toggle_log_returns(),
+                        Action::ToggleLogScale => app.toggle_log_scale(),
+                        Action::CycleIndicatorPage => app.cycle_indicator_page(),
+                        Action::ToggleTicks => app.toggle_ticks(),
+                        Action::ToggleTicker => app.toggle_ticker(),
+                        Action::CycleScenario => {
+                            // Market data first, as the updater takes them
+                            let mut md_vec = market_data.write().unwrap();
+                            let mut ctl = sim_control.write().unwrap();
+                            let scenario = ctl.scenario.map_or(Scenario::Calm, Scenario::next);
+                            let message = sim::apply_scenario(&mut ctl, &mut md_vec, scenario);
+                            info!("{}", message);
+                            alerts.write().unwrap().push(message);
+                        }
+                        Action::ToggleYLock => app.toggle_y_lock(),
+                        Action::ToggleInspect => app.toggle_inspect(),
+                        Action::ResumeLive => app.resume_live(),
+                        Action::OpenCommand => app.open_command(),
+                        Action::PlaybackBack => {
+                            let md_vec = market_data.read().unwrap();
+                            let played = md_vec.iter().map(|md| md.ticks).max().unwrap_or(0);
+                            app.playback_back(played as usize);
+                        }
+                        Action::PlaybackForward => app.playback_forward(),
+                        Action::ToggleCandleGrid => app.toggle_candle_grid(),
+                        Action::NextCandlePage => app.next_candle_page(),
+                        Action::PrevCandlePage => app.prev_candle_page(),
+                        Action::ToggleAbsoluteX => app.toggle_absolute_x(),
+                        Action::ToggleHelp => app.toggle_help(),
+                        Action::ReplayFaster => sim_control.write().unwrap().replay_faster(),
+                        Action::ReplaySlower => sim_control.write().unwrap().replay_slower(),
+                        Action::NewsShock => sim_control.write().unwrap().pending_news = true,
+                        Action::TogglePause => sim_control.write().unwrap().toggle_pause(),
+                        Action::Step => sim_control.write().unwrap().step(),
+                        Action::ToggleSmoothLatency => app.toggle_smooth_latency(),
+                        Action::CycleTimeUnit => app.cycle_time_unit(),
+                        Action::ToggleCorrelation => app.toggle_correlation(),
+                        Action::Snapshot => {
+                            let md_vec = market_data.read().unwrap();
+                            let prices = md_vec.iter().map(|md| *md.price.read().unwrap());
+                            app.take_snapshot(prices.collect());
+                        }
+                        Action::Export => {
+                            let md_vec = market_data.read().unwrap();
+                            let message = match export::export_window(
+                                &md_vec[app.selected],
+                                app.plot_points,
+                                &app.zone,
+                            ) {
+                                Ok(path) => format!("Exported {}", path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                            alerts.write().unwrap().push(message);
+                        }
+                        Action::Buy | Action::Sell | Action::BuySized | Action::SellSized => {
+                            let side = match action {
+                                Action::Buy | Action::BuySized => Side::Buy,
+                                _ => Side::Sell,
+                            };
+                            let md_vec = market_data.read().unwrap();
+                            let md = &md_vec[app.selected];
+                            let qty = match action {
+                                Action::BuySized | Action::SellSized => {
+                                    orders::risk_size(md, app.target_risk, app.return_kind)
+                                        .unwrap_or(ORDER_QTY)
+                                }
+                                _ => ORDER_QTY,
+                            };
+                            let mut orders = blotter.write().unwrap();
+                            match orders.check(&limits, &md_vec, md.count, side, qty) {
+                                Ok(()) => orders.submit(md, side, qty, fill_latency),
+                                Err(breach) => {
+                                    info!("Rejected {} {} {}: {}", side, qty, md.symbol, breach);
+                                    app.notify(breach.to_string());
+                                }
+                            }
+                        }
+                        Action::ResetLatency => {
+                            for md in market_data.write().unwrap().iter_mut() {
+                                md.latency = LatencyStats::default();
+                            }
+                        }
+                        Action::AddStock => {
+                            let n = add_stock(&market_data, &ui_data, &config);
+                            app.set_stock_count(n);
+                            // A held copy has the old stocks, which `selected` may now be past
+                            held = None;
+                        }
+                        Action::RemoveStock => {
+                            let n = remove_stock(&market_data, &ui_data, &blotter);
+                            app.set_stock_count(n);
+                            held = None;
+                        }
+                        Action::NarrowerCandles => app.narrower_candles(),
+                        Action::WiderCandles => app.wider_candles(),
+                        Action::VolUp => sim_control.write().unwrap().vol_up(),
+                        Action::VolDown => sim_control.write().unwrap().vol_down(),
+                    }
+                }
+            }
+            Err(e) => {
+                input_errors += 1;
+                error!("Input error ({}/{}): {:?}", input_errors, MAX_INPUT_ERRORS, e);
+                if input_errors >= MAX_INPUT_ERRORS {
+                    error!("Too many consecutive input errors, shutting down.");
                     break;
                 }
             }
         }
 
-        let md_vec = market_data.read().unwrap().clone();
-        let ui_vec = ui_data.read().unwrap().clone();
-
-        terminal.draw(|f| {
-            let main_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Length(8), Constraint::Min(10)])
-                .split(f.area());
-
-            // --- Pointers ---
-            let mut lines = vec![];
-            for md in md_vec.iter() {
-                let val = *md.price.read().unwrap();
-                lines.push(ratatui::text::Line::from(format!(
-                    "Backend Stock {} -> ptr: {:p}, value: {:.2}",
-                    md.count,
-                    Arc::as_ptr(&md.price),
-                    val
-                )));
-            }
-            for ui in ui_vec.iter() {
-                lines.push(ratatui::text::Line::from(format!(
-                    "Frontend Stock {} -> ptr: {:p}, moving avg: {:.2}",
-                    ui.count,
-                    Arc::as_ptr(&ui.value),
-                    *ui.value
-                )));
-            }
-            f.render_widget(
-                Paragraph::new(lines)
-                    .block(Block::default().borders(Borders::ALL).title("Pointers")),
-                main_chunks[0],
-            );
-
-            // --- Charts ---
-            let chart_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-                .split(main_chunks[1]);
-
-            // Backend chart
-            let md_points: Vec<Vec<(f64, f64)>> = md_vec
-                .iter()
-                .map(|md| md.history.iter().enumerate().map(|(i, y)| (i as f64, *y)).collect())
-                .collect();
-
-            let md_datasets: Vec<Dataset> = md_points
-                .iter()
-                .enumerate()
-                .map(|(i, pts)| {
-                    Dataset::default()
-                        .name(format!("Backend {}", i))
-                        .marker(symbols::Marker::Dot)
-                        .style(Style::default().fg(colors[i]))
-                        .data(pts)
-                })
-                .collect();
-
-            let min_md = md_vec
-                .iter()
-                .flat_map(|x| x.history.iter())
-                .cloned()
-                .fold(f64::INFINITY, f64::min)
-                - 1.0;
-            let max_md = md_vec
-                .iter()
-                .flat_map(|x| x.history.iter())
-                .cloned()
-                .fold(f64::NEG_INFINITY, f64::max)
-                + 1.0;
-
-            let backend_chart = Chart::new(md_datasets)
-                .block(Block::default().borders(Borders::ALL).title("Backend Stocks"))
-                .x_axis(Axis::default().bounds([0.0, HISTORY_LEN as f64]))
-                .y_axis(Axis::default().bounds([min_md, max_md]));
+        if config.duration.is_some_and(|limit| start.elapsed() >= limit) {
+            info!("--duration reached, shutting down.");
+            break;
+        }
+        if !pressed && frame_start.elapsed() < app.pacer.interval() {
+            continue;
+        }
+        frame_start = Instant::now();
 
-            f.render_widget(backend_chart, chart_chunks[0]);
+        if config.notify {
+            notifier.check(&alerts.read().unwrap());
+        }
 
-            // Frontend chart
-            let ui_points: Vec<Vec<(f64, f64)>> = ui_vec
-                .iter()
-                .map(|ui| ui.history.iter().enumerate().map(|(i, y)| (i as f64, *y)).collect())
-                .collect();
+        let version = data_version.load(Ordering::Relaxed);
+        let idle = drawn_version == Some(version) && !pressed;
+        if config.redraw_on_change && idle && last_draw.elapsed() < MAX_IDLE_REDRAW {
+            continue;
+        }
+        drawn_version = Some(version);
+        last_draw = Instant::now();
 
-            let ui_datasets: Vec<Dataset> = ui_points
-                .iter()
-                .enumerate()
-                .map(|(i, pts)| {
-                    Dataset::default()
-                        .name(format!("Frontend {}", i))
-                        .marker(symbols::Marker::Braille)
-                        .style(Style::default().fg(colors[i]))
-                        .data(pts)
-                })
-                .collect();
-
-            let min_ui = ui_vec
-                .iter()
-                .flat_map(|x| x.history.iter())
-                .cloned()
-                .fold(f64::INFINITY, f64::min)
-                - 1.0;
-            let max_ui = ui_vec
-                .iter()
-                .flat_map(|x| x.history.iter())
-                .cloned()
-                .fold(f64::NEG_INFINITY, f64::max)
-                + 1.0;
+        let (md_vec, ui_vec) = if let (Some(tick), Some(recording)) = (app.playback, &recording) {
+            let md_vec = market_data.read().unwrap();
+            let md_vec = md_vec.iter().map(|md| recording.at(md, tick)).collect();
+            app.held_at = None;
+            (md_vec, ui_data.read().unwrap().clone())
+        } else if app.inspecting() {
+            let (at, md_vec, ui_vec) = held.get_or_insert_with(|| {
+                let md_vec = market_data.read().unwrap().clone();
+                (Instant::now(), md_vec, ui_data.read().unwrap().clone())
+            });
+            app.held_at = Some(*at);
+            (md_vec.clone(), ui_vec.clone())
+        } else {
+            held = None;
+            app.held_at = None;
+            (market_data.read().unwrap().clone(), ui_data.read().unwrap().clone())
+        };
+        // Any key may change how the frontend chart is drawn
+        if pressed {
+            app.invalidate_frontend_chart();
+        }
+        app.frontend_version = frontend_version.load(Ordering::Relaxed);
+        let sim = sim_control.read().unwrap().clone();
+        let alert_log = alerts.read().unwrap().clone();
+        let orders = blotter.read().unwrap().clone();
 
-            let frontend_chart = Chart::new(ui_datasets)
-                .block(Block::default().borders(Borders::ALL).title("Frontend Moving Avg"))
-                .x_axis(Axis::default().bounds([0.0, HISTORY_LEN as f64]))
-                .y_axis(Axis::default().bounds([min_ui, max_ui]));
+        let prices: Vec<f64> = md_vec.iter().map(|md| *md.price.read().unwrap()).collect();
+        app.pacer.observe(&prices);
+        let histories: Vec<&[f64]> = md_vec.iter().map(|md| md.history.as_slice()).collect();
+        app.refresh_correlation(&histories);
+        let frame =
+            terminal.draw(|f| ui::draw(f, &app, &sim, &alert_log, &orders, &md_vec, &ui_vec))?;
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.write(frame.buffer)?;
+        }
+        app.advance_ticker();
+    }
 
-            f.render_widget(frontend_chart, chart_chunks[1]);
-        })?;
+    drop(terminal);
+    tick_log_writer.close();
+    let md_vec = market_data.read().unwrap().clone();
+    write_summary(&config, &md_vec)
+}
 
-        thread::sleep(Duration::from_millis(50));
+/// `--summary`: the per-stock session report, if one was asked for.
+fn write_summary(config: &Config, md_vec: &[MarketData]) -> io::Result<()> {
+    match &config.summary {
+        Some(path) => {
+            export::write_summary(path, md_vec)?;
+            info!("Wrote session summary to {}", path.display());
+            Ok(())
+        }
+        None => Ok(()),
     }
-
-    disable_raw_mode()?;
-    terminal.backend_mut().execute(LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-    Ok(())
 }
 
@@ -0,0 +1,282 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::indicators::{self, ReturnKind};
+use crate::MarketData;
+
+/// Quantity of each `b` / `s` order.
+pub const ORDER_QTY: u32 = 1;
+
+/// Units whose one-sigma tick move (the stddev of returns, in price) costs
+/// about `target_risk`, so calmer stocks get bigger orders. At least one
+/// unit; `None` while the stock has not moved yet.
+pub fn risk_size(md: &MarketData, target_risk: f64, kind: ReturnKind) -> Option<u32> {
+    let price = *md.price.read().unwrap();
+    let sigma = indicators::stddev(&kind.of(md.samples())) * price.abs();
+    (sigma > 0.0).then(|| (target_risk / sigma).floor().max(1.0) as u32)
+}
+
+/// Pre-trade exposure caps (`--max-position`, `--max-notional`). Pending
+/// orders count as if filled, so repeated key presses cannot outrun them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Limits {
+    pub max_position: Option<u32>,
+    pub max_notional: Option<f64>,
+}
+
+/// Why an order was rejected.
+#[derive(Debug, PartialEq)]
+pub enum LimitBreach {
+    Position { projected: i64, max: u32 },
+    Notional { projected: f64, max: f64 },
+}
+
+impl fmt::Display for LimitBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitBreach::Position { projected, max } => {
+                write!(f, "limit reached: position {:+} > {}", projected, max)
+            }
+            LimitBreach::Notional { projected, max } => {
+                write!(f, "limit reached: notional {:.2} > {:.2}", projected, max)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    fn sign(self) -> i64 {
+        match self {
+            Side::Buy => 1,
+            Side::Sell => -1,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        })
+    }
+}
+
+/// A manual market order waiting out its simulated latency.
+#[derive(Clone)]
+pub struct Order {
+    pub stock: usize,
+    pub side: Side,
+    pub qty: u32,
+    /// Price showing when the key was pressed, to measure slippage against.
+    pub quoted: f64,
+    pub fill_at: Instant,
+}
+
+#[derive(Clone)]
+pub struct Fill {
+    pub stock: usize,
+    pub side: Side,
+    pub qty: u32,
+    pub quoted: f64,
+    pub price: f64,
+    /// The stock's tick count when it filled, to place it on the chart.
+    pub tick: u64,
+}
+
+impl Fill {
+    /// How much worse than quoted the fill was: positive is a loss for
+    /// either side.
+    pub fn slippage(&self) -> f64 {
+        match self.side {
+            Side::Buy => self.price - self.quoted,
+            Side::Sell => self.quoted - self.price,
+        }
+    }
+}
+
+/// Paper-trading orders, written by the main loop (submits) and the backend
+/// updater (fills) and read by the renderer.
+#[derive(Clone, Default)]
+pub struct Blotter {
+    pending: VecDeque<Order>,
+    fills: Vec<Fill>,
+}
+
+impl Blotter {
+    /// Queues a market order that fills `latency` from now at whatever the
+    /// price is then.
+    pub fn submit(&mut self, md: &MarketData, side: Side, qty: u32, latency: Duration) {
+        self.pending.push_back(Order {
+            stock: md.count,
+            side,
+            qty,
+            quoted: *md.price.read().unwrap(),
+            fill_at: Instant::now() + latency,
+        });
+    }
+
+    /// Net units of `stock` once every pending order has filled.
+    pub fn position(&self, stock: usize) -> i64 {
+        let filled = self.fills.iter().filter(|f| f.stock == stock);
+        let pending = self.pending.iter().filter(|o| o.stock == stock);
+        filled.map(|f| f.side.sign() * f.qty as i64).sum::<i64>()
+            + pending.map(|o| o.side.sign() * o.qty as i64).sum::<i64>()
+    }
+
+    /// Summed |position| × current price over every stock, with an extra
+    /// `delta` units on `stock`.
+    fn notional(&self, md_vec: &[MarketData], stock: usize, delta: i64) -> f64 {
+        md_vec
+            .iter()
+            .map(|md| {
+                let units = self.position(md.count) + if md.count == stock { delta } else { 0 };
+                units.abs() as f64 * md.price.read().unwrap().abs()
+            })
+            .sum()
+    }
+
+    /// Checks an order against `limits` before it is submitted. Only an
+    /// order that grows an exposure past its cap is refused, so one that
+    /// reduces a position (say after the price ran the notional up) always
+    /// goes through.
+    pub fn check(
+        &self,
+        limits: &Limits,
+        md_vec: &[MarketData],
+        stock: usize,
+        side: Side,
+        qty: u32,
+    ) -> Result<(), LimitBreach> {
+        let delta = side.sign() * qty as i64;
+        let current = self.position(stock);
+        let projected = current + delta;
+        if let Some(max) = limits.max_position {
+            if projected.abs() > max as i64 && projected.abs() > current.abs() {
+                return Err(LimitBreach::Position { projected, max });
+            }
+        }
+        if let Some(max) = limits.max_notional {
+            let projected = self.notional(md_vec, stock, delta);
+            if projected > max && projected > self.notional(md_vec, stock, 0) {
+                return Err(LimitBreach::Notional { projected, max });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills every order whose deadline has passed at the prevailing price.
+    /// Called by the updater while it holds the market data lock, so fills
+    /// see the same prices as the tick that just happened.
+    pub fn fill_due(&mut self, now: Instant, md_vec: &[MarketData]) -> Vec<Fill> {
+        let mut filled = vec![];
+        while self.pending.front().is_some_and(|o| o.fill_at <= now) {
+            let order = self.pending.pop_front().unwrap();
+            filled.push(Fill {
+                stock: order.stock,
+                side: order.side,
+                qty: order.qty,
+                quoted: order.quoted,
+                price: *md_vec[order.stock].price.read().unwrap(),
+                tick: md_vec[order.stock].ticks,
+            });
+        }
+        self.fills.extend(filled.iter().cloned());
+        filled
+    }
+
+    /// Deadline of the oldest pending order, so the updater can wake for it.
+    pub fn next_fill(&self) -> Option<Instant> {
+        self.pending.front().map(|o| o.fill_at)
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &Order> {
+        self.pending.iter()
+    }
+
+    /// Up to `n` fills, newest first.
+    pub fn recent_fills(&self, n: usize) -> impl Iterator<Item = &Fill> {
+        self.fills.iter().rev().take(n)
+    }
+
+    /// Up to `n` fills of one stock, newest first.
+    pub fn recent_fills_of(&self, stock: usize, n: usize) -> impl Iterator<Item = &Fill> {
+        self.fills
+            .iter()
+            .rev()
+            .filter(move |f| f.stock == stock)
+            .take(n)
+    }
+
+    /// Drops the pending orders and fills of a stock that was removed.
+    pub fn forget_stock(&mut self, stock: usize) {
+        self.pending.retain(|o| o.stock != stock);
+        self.fills.retain(|f| f.stock != stock);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty() && self.fills.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    use crate::config::Config;
+    use crate::instruments;
+
+    fn stocks() -> Vec<MarketData> {
+        let config = Config::parse_from(["rust_hft_tui"]);
+        instruments::defaults()
+            .iter()
+            .enumerate()
+            .map(|(i, inst)| MarketData::new(i, inst, &config))
+            .collect()
+    }
+
+    #[test]
+    fn orders_up_to_the_cap_pass_and_one_past_it_is_rejected() {
+        let md_vec = stocks();
+        let limits = Limits {
+            max_position: Some(3),
+            max_notional: None,
+        };
+        let mut blotter = Blotter::default();
+        blotter.submit(&md_vec[0], Side::Buy, 2, Duration::ZERO);
+        assert_eq!(blotter.check(&limits, &md_vec, 0, Side::Buy, 1), Ok(()));
+        blotter.submit(&md_vec[0], Side::Buy, 1, Duration::ZERO);
+        assert_eq!(
+            blotter.check(&limits, &md_vec, 0, Side::Buy, 1),
+            Err(LimitBreach::Position {
+                projected: 4,
+                max: 3
+            })
+        );
+        // Selling back down is always allowed
+        assert_eq!(blotter.check(&limits, &md_vec, 0, Side::Sell, 1), Ok(()));
+    }
+
+    #[test]
+    fn notional_cap_counts_every_stock() {
+        let md_vec = stocks();
+        let price = |i: usize| *md_vec[i].price.read().unwrap();
+        let limits = Limits {
+            max_position: None,
+            max_notional: Some(price(0) + price(1)),
+        };
+        let mut blotter = Blotter::default();
+        blotter.submit(&md_vec[0], Side::Sell, 1, Duration::ZERO);
+        assert_eq!(blotter.check(&limits, &md_vec, 1, Side::Buy, 1), Ok(()));
+        blotter.submit(&md_vec[1], Side::Buy, 1, Duration::ZERO);
+        assert!(blotter.check(&limits, &md_vec, 2, Side::Buy, 1).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! Tiling of the chart area into independently assigned panes.
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+/// Most panes on screen at once (a 2×2 grid).
+const MAX_PANES: usize = 4;
+/// Deepest a pane may be nested, so splits stay within 2×2.
+const MAX_DEPTH: usize = 2;
+
+/// What a pane plots.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Content {
+    Backend,
+    Frontend,
+    Returns,
+    Rsi,
+    Candles,
+}
+
+impl Content {
+    fn next(self) -> Self {
+        match self {
+            Content::Backend => Content::Frontend,
+            Content::Frontend => Content::Returns,
+            Content::Returns => Content::Rsi,
+            Content::Rsi => Content::Candles,
+            Content::Candles => Content::Backend,
+        }
+    }
+}
+
+/// Binary split tree; leaves are numbered left to right (depth first).
+pub enum Pane {
+    Leaf(Content),
+    Split(Direction, Box<[Pane; 2]>),
+}
+
+impl Pane {
+    /// Two panes replacing the classic backend | frontend view.
+    pub fn first_split(direction: Direction) -> Self {
+        Pane::Split(
+            direction,
+            Box::new([Pane::Leaf(Content::Backend), Pane::Leaf(Content::Frontend)]),
+        )
+    }
+
+    pub fn leaves(&self) -> usize {
+        match self {
+            Pane::Leaf(_) => 1,
+            Pane::Split(_, children) => children.iter().map(Pane::leaves).sum(),
+        }
+    }
+
+    /// Splits leaf `index` in two, the new half showing the next content.
+    /// Does nothing once the 2×2 limit is reached.
+    pub fn split(&mut self, index: usize, direction: Direction) {
+        if self.leaves() < MAX_PANES {
+            self.split_at(index, direction, 0);
+        }
+    }
+
+    fn split_at(&mut self, index: usize, direction: Direction, depth: usize) {
+        match self {
+            Pane::Leaf(content) if index == 0 && depth < MAX_DEPTH => {
+                let content = *content;
+                *self = Pane::Split(
+                    direction,
+                    Box::new([Pane::Leaf(content), Pane::Leaf(content.next())]),
+                );
+            }
+            Pane::Leaf(_) => {}
+            Pane::Split(_, children) => {
+                let left = children[0].leaves();
+                if index < left {
+                    children[0].split_at(index, direction, depth + 1);
+                } else {
+                    children[1].split_at(index - left, direction, depth + 1);
+                }
+            }
+        }
+    }
+
+    /// Removes leaf `index`, its sibling taking the freed space. Returns
+    /// `None` when the last pane is closed.
+    pub fn close(self, index: usize) -> Option<Self> {
+        match self {
+            Pane::Leaf(_) => None,
+            Pane::Split(direction, children) => {
+                let [a, b] = *children;
+                let left = a.leaves();
+                let (a, b) = if index < left {
+                    (a.close(index), Some(b))
+                } else {
+                    (Some(a), b.close(index - left))
+                };
+                match (a, b) {
+                    (Some(a), Some(b)) => Some(Pane::Split(direction, Box::new([a, b]))),
+                    (only, None) | (None, only) => only,
+                }
+            }
+        }
+    }
+
+    /// Moves leaf `index` on to its next content.
+    pub fn cycle_content(&mut self, index: usize) {
+        match self {
+            Pane::Leaf(content) if index == 0 => *content = content.next(),
+            Pane::Leaf(_) => {}
+            Pane::Split(_, children) => {
+                let left = children[0].leaves();
+                if index < left {
+                    children[0].cycle_content(index);
+                } else {
+                    children[1].cycle_content(index - left);
+                }
+            }
+        }
+    }
+
+    /// Area and content of every leaf, in leaf order.
+    pub fn layout(&self, area: Rect) -> Vec<(Rect, Content)> {
+        match self {
+            Pane::Leaf(content) => vec![(area, *content)],
+            Pane::Split(direction, children) => {
+                let halves = Layout::default()
+                    .direction(*direction)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                children
+                    .iter()
+                    .zip(halves.iter())
+                    .flat_map(|(child, half)| child.layout(*half))
+                    .collect()
+            }
+        }
+    }
+}
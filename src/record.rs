@@ -0,0 +1,90 @@
+//! Flipbook recording of the rendered screen (`--record`) and its playback
+//! (`--play`), for demos without a screen recorder.
+//!
+//! The file is plain text: each frame is a `=== frame <n> <ms>ms <w>x<h>`
+//! header line followed by `<h>` lines of the rendered cells.
+
+use std::fs::{self, File};
+use std::io::{self, stdout, BufWriter, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    cursor::MoveTo,
+    terminal::{Clear, ClearType},
+    QueueableCommand,
+};
+use ratatui::buffer::Buffer;
+
+const HEADER: &str = "=== frame ";
+
+/// Appends every drawn frame to a file.
+pub struct Recorder {
+    out: BufWriter<File>,
+    start: Instant,
+    frames: u64,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            start: Instant::now(),
+            frames: 0,
+        })
+    }
+
+    /// Writes the buffer of a completed draw, flushing so the file is
+    /// usable even if the app is killed rather than quit.
+    pub fn write(&mut self, buffer: &Buffer) -> io::Result<()> {
+        let area = buffer.area;
+        writeln!(
+            self.out,
+            "{}{} {}ms {}x{}",
+            HEADER,
+            self.frames,
+            self.start.elapsed().as_millis(),
+            area.width,
+            area.height
+        )?;
+        for y in area.top()..area.bottom() {
+            let row: String = (area.left()..area.right())
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            writeln!(self.out, "{}", row)?;
+        }
+        self.frames += 1;
+        self.out.flush()
+    }
+}
+
+/// Shows a recording on stdout at its original pace.
+pub fn play(path: &Path) -> io::Result<()> {
+    let content = fs::read_to_string(path)?;
+    let mut stdout = stdout();
+    let start = Instant::now();
+    for frame in content.split(HEADER).filter(|f| !f.is_empty()) {
+        let (header, rows) = frame.split_once('\n').unwrap_or((frame, ""));
+        let at = header
+            .split_whitespace()
+            .nth(1)
+            .and_then(|ms| ms.strip_suffix("ms"))
+            .and_then(|ms| ms.parse().ok())
+            .map(Duration::from_millis)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("bad frame header: {}", header),
+                )
+            })?;
+        thread::sleep((start + at).saturating_duration_since(Instant::now()));
+        stdout.queue(Clear(ClearType::All))?.queue(MoveTo(0, 0))?;
+        for row in rows.lines() {
+            // Raw `\n` would not return to column 0 in every terminal
+            write!(stdout, "{}\r\n", row)?;
+        }
+        stdout.flush()?;
+    }
+    Ok(())
+}
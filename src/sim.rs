@@ -0,0 +1,887 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use log::{error, info};
+use rand::rngs::{OsRng, StdRng};
+use rand::{Rng, RngCore, SeedableRng};
+use serde::Deserialize;
+
+use crate::alerts::AlertLog;
+use crate::indicators::{self, Candle};
+use crate::MarketData;
+
+pub const MIN_REPLAY_SPEED: f64 = 0.25;
+pub const MAX_REPLAY_SPEED: f64 = 16.0;
+/// Size of an injected news shock, as a fraction of price.
+pub const NEWS_SHOCK: f64 = 0.02;
+pub const MIN_VOL: f64 = 0.25;
+pub const MAX_VOL: f64 = 5.0;
+/// Change of the volatility multiplier per `<` / `>` press.
+const VOL_STEP: f64 = 0.25;
+/// Minutes in a simulated day.
+const DAY_MINUTES: u32 = 24 * 60;
+/// Seed used when the OS has no entropy to give, so the run still starts.
+const FALLBACK_SEED: u64 = 0x5eed;
+/// Price levels per side of the synthetic order book.
+pub const BOOK_LEVELS: usize = 5;
+/// Mean resting size at a book level.
+const BOOK_SIZE: f64 = 100.0;
+
+/// Knobs shared between the main loop (which sets them from key presses) and
+/// the backend updater (which reads them every tick).
+#[derive(Clone)]
+pub struct SimControl {
+    pub replaying: bool,
+    pub replay_speed: f64,
+    /// Set by the `n` key, consumed by the updater on its next pass.
+    pub pending_news: bool,
+    /// Scales every stock's sigma; adjusted live with `<` / `>`.
+    pub vol: f64,
+    /// Toggled with `Space`; the updater stops ticking until resumed.
+    pub paused: bool,
+    /// Single ticks requested with `.` while paused, not yet taken.
+    pending_steps: u32,
+    /// What the RNG was seeded with, for `--seed` to repeat the run.
+    pub seed: u64,
+    /// The RNG fell back to `FALLBACK_SEED`, so every run walks the same way.
+    pub fixed_seed: bool,
+    /// Simulated trading hours (`--session`), advanced by the updater.
+    pub session: Option<Session>,
+    /// Drift, reversion and jumps the random walk runs with.
+    pub regime: Regime,
+    /// Preset `regime` and `vol` came from (`--scenario`, `M`), until the
+    /// volatility is changed by hand.
+    pub scenario: Option<Scenario>,
+}
+
+impl SimControl {
+    pub fn new(replaying: bool) -> Self {
+        Self {
+            replaying,
+            replay_speed: 1.0,
+            pending_news: false,
+            vol: 1.0,
+            paused: false,
+            pending_steps: 0,
+            seed: 0,
+            fixed_seed: false,
+            session: None,
+            regime: Regime::default(),
+            scenario: None,
+        }
+    }
+
+    pub fn replay_faster(&mut self) {
+        self.replay_speed = (self.replay_speed * 2.0).min(MAX_REPLAY_SPEED);
+    }
+
+    pub fn replay_slower(&mut self) {
+        self.replay_speed = (self.replay_speed / 2.0).max(MIN_REPLAY_SPEED);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_steps = 0;
+    }
+
+    /// Queues one tick of every stock; ignored unless paused.
+    pub fn step(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    /// Consumes one queued step, if any. Called by the updater.
+    pub fn take_step(&mut self) -> bool {
+        if self.pending_steps == 0 {
+            return false;
+        }
+        self.pending_steps -= 1;
+        true
+    }
+
+    pub fn vol_up(&mut self) {
+        self.vol = (self.vol + VOL_STEP).min(MAX_VOL);
+        self.scenario = None;
+    }
+
+    pub fn vol_down(&mut self) {
+        self.vol = (self.vol - VOL_STEP).max(MIN_VOL);
+        self.scenario = None;
+    }
+}
+
+/// The random walk's parameters beyond each stock's sigma.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Regime {
+    /// Mean move per tick as a fraction of the stock's (scaled) sigma.
+    pub drift: f64,
+    /// Fraction of the gap to `MarketData::anchor` closed per tick.
+    pub revert: f64,
+    pub jumps: Option<Jumps>,
+}
+
+/// Drop `FlashCrashRecovery` opens with, before it pulls prices back.
+const FLASH_CRASH: f64 = 0.10;
+
+/// Curated price-model settings (`--scenario`, `M` to cycle), for demos and
+/// for trying features against different markets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Scenario {
+    /// Half the usual volatility, no drift or jumps.
+    Calm,
+    /// A steady upward drift.
+    Trending,
+    /// Heavy volatility with frequent gaps.
+    Volatile,
+    /// Prices slide down, lurching lower now and then.
+    Crash,
+    /// Everything drops 10% at once, then climbs back towards where it was.
+    FlashCrashRecovery,
+}
+
+impl Scenario {
+    pub fn next(self) -> Self {
+        match self {
+            Scenario::Calm => Scenario::Trending,
+            Scenario::Trending => Scenario::Volatile,
+            Scenario::Volatile => Scenario::Crash,
+            Scenario::Crash => Scenario::FlashCrashRecovery,
+            Scenario::FlashCrashRecovery => Scenario::Calm,
+        }
+    }
+
+    /// `vol` multiplier and regime.
+    fn settings(self) -> (f64, Regime) {
+        let jumps = |rate, size| Some(Jumps { rate, size });
+        let (vol, drift, revert, jumps) = match self {
+            Scenario::Calm => (0.5, 0.0, 0.0, None),
+            Scenario::Trending => (1.0, 0.2, 0.0, None),
+            Scenario::Volatile => (2.5, 0.0, 0.0, jumps(0.1, 0.03)),
+            Scenario::Crash => (2.0, -0.4, 0.0, jumps(0.05, 0.04)),
+            Scenario::FlashCrashRecovery => (1.5, 0.0, 0.05, None),
+        };
+        let regime = Regime {
+            drift,
+            revert,
+            jumps,
+        };
+        (vol, regime)
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Scenario::Calm => "calm",
+            Scenario::Trending => "trending",
+            Scenario::Volatile => "volatile",
+            Scenario::Crash => "crash",
+            Scenario::FlashCrashRecovery => "flash-crash-recovery",
+        })
+    }
+}
+
+/// Switches every stock to `scenario`'s settings, live. The caller holds
+/// both write locks, so no tick runs on a mix of the old and new settings.
+/// Returns a description for the alerts panel.
+pub fn apply_scenario(
+    ctl: &mut SimControl,
+    md_vec: &mut [MarketData],
+    scenario: Scenario,
+) -> String {
+    let (vol, regime) = scenario.settings();
+    ctl.vol = vol;
+    ctl.regime = regime;
+    ctl.scenario = Some(scenario);
+    for md in md_vec.iter_mut() {
+        md.anchor = None;
+        if scenario == Scenario::FlashCrashRecovery && !md.hidden {
+            let mut p = md.price.write().unwrap();
+            md.anchor = Some(*p);
+            let delta = -FLASH_CRASH * p.abs();
+            shift_price(&mut p, &mut md.grid, delta, md.allow_negative, md.band);
+        }
+    }
+    format!("Scenario: {}", scenario)
+}
+
+/// Price held as a whole number of `--tick-size` units. Moves are rounded
+/// to whole ticks and summed as integers, so a price can never drift off the
+/// grid; the `f64` in `MarketData.price` is only its display value.
+#[derive(Clone, Copy)]
+pub struct TickGrid {
+    size: f64,
+    units: i64,
+}
+
+impl TickGrid {
+    pub fn new(size: f64, price: f64) -> Self {
+        Self {
+            size,
+            units: (price / size).round() as i64,
+        }
+    }
+
+    pub fn price(&self) -> f64 {
+        self.units as f64 * self.size
+    }
+
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+}
+
+/// Trading hours on a simulated clock. The clock is decoupled from wall
+/// time: it starts at the open and moves `step` minutes every updater pass
+/// that ticks, so a whole day can go by in a few minutes of real time.
+#[derive(Clone, Copy)]
+pub struct Session {
+    open: u32,
+    close: u32,
+    step: u32,
+    /// Current simulated time, in minutes after midnight.
+    minute: u32,
+}
+
+impl Session {
+    pub fn new((open, close): (u32, u32), step: u32) -> Self {
+        Self {
+            open,
+            close,
+            step,
+            minute: open,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        if self.open < self.close {
+            (self.open..self.close).contains(&self.minute)
+        } else {
+            // Overnight session
+            self.minute >= self.open || self.minute < self.close
+        }
+    }
+
+    pub fn advance(&mut self) {
+        self.minute = (self.minute + self.step) % DAY_MINUTES;
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02} {}",
+            self.minute / 60,
+            self.minute % 60,
+            if self.is_open() { "open" } else { "closed" }
+        )
+    }
+}
+
+/// Ticks a stock spent with its session closed, as inclusive runs, so the
+/// charts can shade them. Runs older than the history are dropped.
+#[derive(Clone, Default)]
+pub struct ClosedTicks(Vec<(u64, u64)>);
+
+impl ClosedTicks {
+    pub fn mark(&mut self, tick: u64, history_len: usize) {
+        match self.0.last_mut() {
+            Some((_, end)) if *end + 1 == tick => *end = tick,
+            _ => self.0.push((tick, tick)),
+        }
+        let oldest = tick.saturating_sub(history_len as u64);
+        self.0.retain(|&(_, end)| end >= oldest);
+    }
+
+    pub fn contains(&self, tick: u64) -> bool {
+        self.0
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&tick))
+    }
+}
+
+/// The simulation's RNG and the seed it was made from: `seed` (`--seed`)
+/// if given, else one drawn from the OS. Either way it goes through
+/// `seed_from_u64`, so passing the returned seed to `--seed` walks the same
+/// way again. Where the OS has no entropy (e.g. a CI sandbox) it falls back
+/// to a fixed seed instead of panicking; the flag tells the caller so it
+/// can warn.
+pub fn new_rng(seed: Option<u64>) -> (StdRng, u64, bool) {
+    let (seed, fixed) = match seed {
+        Some(seed) => (seed, false),
+        None => {
+            let mut bytes = [0; 8];
+            match OsRng.try_fill_bytes(&mut bytes) {
+                Ok(()) => (u64::from_le_bytes(bytes), false),
+                Err(e) => {
+                    error!("Could not seed RNG from the OS ({}), using fixed seed", e);
+                    (FALLBACK_SEED, true)
+                }
+            }
+        }
+    };
+    (StdRng::seed_from_u64(seed), seed, fixed)
+}
+
+/// Synthetic top of book: resting size on the best `BOOK_LEVELS` bids and
+/// asks, nearest level first. There is no matching engine behind it; sizes
+/// are redrawn every tick, leaning towards whichever side the price just
+/// moved to, so the book hints at pressure the way a real one would.
+#[derive(Clone, Default)]
+pub struct Book {
+    pub bids: [u32; BOOK_LEVELS],
+    pub asks: [u32; BOOK_LEVELS],
+}
+
+impl Book {
+    /// New sizes after a tick; `tilt` in -1..=1 is the move as a fraction of
+    /// its largest possible size, positive loading the bid.
+    pub fn after_tick(rng: &mut impl Rng, tilt: f64) -> Self {
+        let mut level =
+            |lean: f64| (BOOK_SIZE * (1.0 + lean / 2.0) * rng.gen_range(0.5..1.5)).round() as u32;
+        Self {
+            bids: std::array::from_fn(|_| level(tilt)),
+            asks: std::array::from_fn(|_| level(-tilt)),
+        }
+    }
+
+    /// Order-flow imbalance over the whole visible book.
+    pub fn imbalance(&self) -> f64 {
+        let bid: u32 = self.bids.iter().sum();
+        let ask: u32 = self.asks.iter().sum();
+        indicators::imbalance(bid as f64, ask as f64)
+    }
+}
+
+/// Units traded on a tick: around `BOOK_SIZE`, and up to twice that on
+/// the largest moves (`tilt` as in `Book::after_tick`), as volume follows
+/// volatility on a real tape.
+pub fn tick_volume(rng: &mut impl Rng, tilt: f64) -> u64 {
+    (BOOK_SIZE * (1.0 + tilt.abs()) * rng.gen_range(0.5..1.5)).round() as u64
+}
+
+/// What happens to a random-walk stock whose price hits zero, where it
+/// would otherwise sit with every downward move clamped away
+/// (`--zero-policy`). Stocks in `--allow-negative` never hit it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ZeroPolicy {
+    /// Stay at zero until an upward move (the original behaviour).
+    Clamp,
+    /// Bounce back up by as far as the move overshot zero.
+    Reflect,
+    /// Start over at the instrument's initial price.
+    Reseed,
+    /// Stop ticking and drop out of the charts.
+    Hide,
+}
+
+/// Price range a stock is kept in (`--price-bands`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Band {
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Band {
+    /// Folds `price` back into the band as if it bounced off the edges, so a
+    /// move past one ends up as far inside: 112 in 90–110 becomes 108.
+    pub fn reflect(&self, price: f64) -> f64 {
+        let span = self.high - self.low;
+        let folded = (price - self.low).rem_euclid(2.0 * span);
+        self.low
+            + if folded > span {
+                2.0 * span - folded
+            } else {
+                folded
+            }
+    }
+}
+
+/// Moves `price` by `delta`, in whole ticks when the stock is on a grid,
+/// and floors it at zero unless negative prices are allowed. With a band
+/// the result is then reflected into it (and re-snapped to the grid).
+pub fn shift_price(
+    price: &mut f64,
+    grid: &mut Option<TickGrid>,
+    delta: f64,
+    allow_negative: bool,
+    band: Option<Band>,
+) {
+    match grid {
+        Some(grid) => {
+            grid.units += (delta / grid.size).round() as i64;
+            if !allow_negative {
+                grid.units = grid.units.max(0);
+            }
+            *price = grid.price();
+        }
+        None => {
+            *price += delta;
+            if !allow_negative {
+                *price = price.max(0.0);
+            }
+        }
+    }
+    if let Some(band) = band {
+        set_price(price, grid, band.reflect(*price));
+    }
+}
+
+/// Sets `price` outright (e.g. from a replay), snapped onto the grid if any.
+pub fn set_price(price: &mut f64, grid: &mut Option<TickGrid>, value: f64) {
+    *price = match grid {
+        Some(grid) => {
+            *grid = TickGrid::new(grid.size, value);
+            grid.price()
+        }
+        None => value,
+    };
+}
+
+/// Jump-diffusion on top of the random walk (`--jump-rate`,
+/// `--jump-size`): each stock gaps at Poisson-timed moments, by a random
+/// fraction of its price either way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Jumps {
+    /// Expected jumps per stock per second.
+    pub rate: f64,
+    /// Mean jump as a fraction of the price; sizes are uniform up to
+    /// twice this.
+    pub size: f64,
+}
+
+impl Jumps {
+    /// The jump landing on a tick `dt` after the stock's last one at
+    /// `price`, if any. A Poisson process with `rate` puts at least one in
+    /// `dt` with probability 1 - e^(-rate·dt); two in one tick count as one.
+    pub fn sample(&self, rng: &mut impl Rng, price: f64, dt: Duration) -> Option<f64> {
+        let p = 1.0 - (-self.rate * dt.as_secs_f64()).exp();
+        if !rng.gen_bool(p.clamp(0.0, 1.0)) {
+            return None;
+        }
+        let sign = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+        Some(sign * rng.gen_range(0.0..=2.0 * self.size) * price.abs())
+    }
+}
+
+/// Why a tick's price cannot be used, if it cannot: not a number at all,
+/// or below zero on a stock outside `--allow-negative`.
+pub fn bad_print(price: f64, allow_negative: bool) -> Option<&'static str> {
+    if !price.is_finite() {
+        Some("non-finite")
+    } else if price < 0.0 && !allow_negative {
+        Some("negative")
+    } else {
+        None
+    }
+}
+
+/// A bad tick `--inject-faults` can produce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The price comes through as NaN.
+    Nan,
+    /// The tick never arrives, so the stock goes stale.
+    Dropped,
+    /// The feed repeats its last price for a run of ticks.
+    Frozen,
+    /// The price comes through below zero.
+    Negative,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Fault::Nan => "NaN price",
+            Fault::Dropped => "dropped tick",
+            Fault::Frozen => "frozen feed",
+            Fault::Negative => "negative price",
+        })
+    }
+}
+
+/// `--inject-faults`: corrupts a random `rate` of ticks, to check the
+/// guards against bad data catch each kind of fault.
+pub struct FaultInjector {
+    rate: f64,
+    /// Ticks a frozen feed repeats for.
+    freeze_len: u64,
+    /// Ticks each stock still has to stay frozen, by stock number.
+    frozen: Vec<u64>,
+}
+
+impl FaultInjector {
+    pub fn new(rate: f64, freeze_len: u64) -> Self {
+        Self {
+            rate,
+            freeze_len,
+            frozen: vec![],
+        }
+    }
+
+    /// Whether stock `count`'s next tick repeats the last price, as part of
+    /// a freeze already under way.
+    pub fn freezing(&mut self, count: usize) -> bool {
+        match self.frozen.get_mut(count) {
+            Some(left) if *left > 0 => {
+                *left -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The fault to put on stock `count`'s next tick, if any. A freeze
+    /// covers that tick and the `freeze_len - 1` after it. Stocks that may
+    /// go negative never get `Negative`, which is no fault for them.
+    pub fn next(
+        &mut self,
+        rng: &mut impl Rng,
+        count: usize,
+        allow_negative: bool,
+    ) -> Option<Fault> {
+        if !rng.gen_bool(self.rate) {
+            return None;
+        }
+        let kinds = if allow_negative { 3 } else { 4 };
+        let fault = match rng.gen_range(0..kinds) {
+            0 => Fault::Nan,
+            1 => Fault::Dropped,
+            2 => Fault::Frozen,
+            _ => Fault::Negative,
+        };
+        if fault == Fault::Frozen {
+            if self.frozen.len() <= count {
+                self.frozen.resize(count + 1, 0);
+            }
+            self.frozen[count] = self.freeze_len.saturating_sub(1);
+        }
+        Some(fault)
+    }
+}
+
+/// Moves a random, non-empty subset of stocks by the same ±`NEWS_SHOCK`
+/// in one go, which is what makes the shock a common factor. The caller
+/// holds the market data write lock so no tick sees a half-applied shock.
+/// Returns a description for the alerts panel.
+pub fn apply_news_shock(md_vec: &mut [MarketData], rng: &mut impl Rng) -> String {
+    let sign = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+    let forced = rng.gen_range(0..md_vec.len());
+    let mut hit = vec![];
+    for (i, md) in md_vec.iter_mut().enumerate() {
+        if i != forced && !rng.gen_bool(2.0 / 3.0) {
+            continue;
+        }
+        let mut p = md.price.write().unwrap();
+        let delta = sign * NEWS_SHOCK * p.abs();
+        shift_price(&mut p, &mut md.grid, delta, md.allow_negative, md.band);
+        hit.push(md.symbol.clone());
+    }
+    format!(
+        "News shock {:+.0}% on {}",
+        sign * NEWS_SHOCK * 100.0,
+        hit.join(", ")
+    )
+}
+
+/// Recorded prices played back in place of the random walk, one queue per
+/// stock. Reads the same `stock_id,price` lines the tick log writes, takes
+/// them live from stdin (`--stdin`), or plays whole bars (`--replay-ohlc`).
+pub struct Replay {
+    queues: Vec<VecDeque<f64>>,
+    /// `--replay-ohlc` bars, one per tick; empty in the other modes.
+    bars: Vec<VecDeque<Ohlc>>,
+    live: Option<Receiver<(usize, f64)>>,
+}
+
+/// One pre-aggregated bar of a `--replay-ohlc` file, shown as a candle as
+/// it stands; its close is the stock's price for the tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ohlc {
+    /// As written in the file; only ever displayed.
+    pub time: String,
+    pub candle: Candle,
+}
+
+/// JSON form of a `--stdin` line.
+#[derive(Deserialize)]
+struct StdinTick {
+    symbol: String,
+    price: f64,
+}
+
+impl Replay {
+    pub fn load(path: &Path, n_stocks: usize) -> io::Result<Self> {
+        let mut queues = vec![VecDeque::new(); n_stocks];
+        for line in fs::read_to_string(path)?.lines() {
+            let Some((id, price)) = line.split_once(',') else {
+                continue;
+            };
+            match (id.trim().parse::<usize>(), price.trim().parse::<f64>()) {
+                (Ok(id), Ok(price)) if id < n_stocks => queues[id].push_back(price),
+                _ => error!("Skipping replay line: {}", line),
+            }
+        }
+        Ok(Self {
+            queues,
+            bars: vec![],
+            live: None,
+        })
+    }
+
+    /// Reads `time,open,high,low,close` bars, the n-th file for stock n. A
+    /// leading header line is skipped, as are bars whose high and low do not
+    /// contain the open and close.
+    pub fn load_ohlc(paths: &[PathBuf], n_stocks: usize) -> io::Result<Self> {
+        let mut bars = vec![VecDeque::new(); n_stocks];
+        if paths.len() > n_stocks {
+            error!(
+                "{} --replay-ohlc files for {} stocks; ignoring the rest",
+                paths.len(),
+                n_stocks
+            );
+        }
+        for (queue, path) in bars.iter_mut().zip(paths) {
+            for (n, line) in fs::read_to_string(path)?.lines().enumerate() {
+                match parse_ohlc_line(line) {
+                    Some(bar) => queue.push_back(bar),
+                    None if n == 0 || line.trim().is_empty() => {}
+                    None => error!("Skipping {} line {}: {}", path.display(), n + 1, line),
+                }
+            }
+        }
+        Ok(Self {
+            queues: vec![VecDeque::new(); n_stocks],
+            bars,
+            live: None,
+        })
+    }
+
+    /// Queues `SYMBOL PRICE` or `{"symbol": .., "price": ..}` lines from
+    /// stdin as a reader thread receives them. At EOF the queues simply stop
+    /// filling, so each stock holds its last price.
+    pub fn stdin(symbols: Vec<String>, alerts: Arc<RwLock<AlertLog>>) -> Self {
+        let queues = vec![VecDeque::new(); symbols.len()];
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                match parse_stdin_line(&line, &symbols) {
+                    Some(tick) => {
+                        if tx.send(tick).is_err() {
+                            return;
+                        }
+                    }
+                    None if line.trim().is_empty() => {}
+                    None => error!("Skipping stdin line: {}", line),
+                }
+            }
+            info!("End of stdin; prices now hold");
+            alerts
+                .write()
+                .unwrap()
+                .push("stdin closed: prices holding".to_string());
+        });
+        Self {
+            queues,
+            bars: vec![],
+            live: Some(rx),
+        }
+    }
+
+    /// True when the prices are new rather than read back from a recording.
+    pub fn is_live(&self) -> bool {
+        self.live.is_some()
+    }
+
+    /// The stock's next recorded price, one per tick. From `--stdin` it is
+    /// the newest line received since the last tick instead: a producer
+    /// faster than the tick rate would otherwise pile up a backlog the
+    /// display falls ever further behind.
+    pub fn next_price(&mut self, stock_id: usize) -> Option<f64> {
+        if let Some(rx) = &self.live {
+            for (id, price) in rx.try_iter() {
+                let queue = &mut self.queues[id];
+                queue.clear();
+                queue.push_back(price);
+            }
+        }
+        self.queues.get_mut(stock_id)?.pop_front()
+    }
+
+    pub fn next_bar(&mut self, stock_id: usize) -> Option<Ohlc> {
+        self.bars.get_mut(stock_id)?.pop_front()
+    }
+
+    /// Everything loaded from a file, before any of it is played; `None`
+    /// for `--stdin`, which has no end to scrub to.
+    pub fn recording(&self) -> Option<Recording> {
+        if self.is_live() {
+            return None;
+        }
+        let bars: Vec<Vec<Ohlc>> = self
+            .bars
+            .iter()
+            .map(|q| q.iter().cloned().collect())
+            .collect();
+        let prices = if bars.is_empty() {
+            self.queues
+                .iter()
+                .map(|q| q.iter().copied().collect())
+                .collect()
+        } else {
+            bars.iter()
+                .map(|b| b.iter().map(|bar| bar.candle.close).collect())
+                .collect()
+        };
+        Some(Recording { prices, bars })
+    }
+}
+
+/// A loaded replay in full, for drawing the charts as they stood at any
+/// tick of it (the playback cursor) while the replay itself plays on.
+pub struct Recording {
+    prices: Vec<Vec<f64>>,
+    bars: Vec<Vec<Ohlc>>,
+}
+
+impl Recording {
+    /// Ticks in the longest stock's recording.
+    pub fn len(&self) -> usize {
+        self.prices.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// `md` as it was after `tick` ticks of the recording, its history as
+    /// long as now and padded with the start price before the first tick.
+    /// Stocks the recording does not cover are left as they are.
+    pub fn at(&self, md: &MarketData, tick: usize) -> MarketData {
+        let mut md = md.clone();
+        let Some(prices) = self.prices.get(md.count) else {
+            return md;
+        };
+        let played = &prices[..tick.min(prices.len())];
+        let len = md.history.len();
+        let shown = &played[played.len().saturating_sub(len)..];
+        md.history = std::iter::repeat_n(md.initial_price, len - shown.len())
+            .chain(shown.iter().copied())
+            .collect();
+        md.times = vec![Instant::now(); len];
+        md.volumes = vec![0; len];
+        md.ticks = played.len() as u64;
+        // A price of its own, so the live one is not overwritten
+        md.price = Arc::new(RwLock::new(
+            played.last().copied().unwrap_or(md.initial_price),
+        ));
+        md.bars = match self.bars.get(md.count) {
+            Some(bars) => bars[played.len().saturating_sub(len)..played.len()].to_vec(),
+            None => vec![],
+        };
+        md
+    }
+}
+
+fn parse_ohlc_line(line: &str) -> Option<Ohlc> {
+    let mut fields = line.split(',').map(str::trim);
+    let time = fields.next()?.to_string();
+    let mut price = || fields.next()?.parse::<f64>().ok().filter(|p| p.is_finite());
+    let candle = Candle {
+        open: price()?,
+        high: price()?,
+        low: price()?,
+        close: price()?,
+    };
+    let body = (candle.open.min(candle.close), candle.open.max(candle.close));
+    (candle.low <= body.0 && body.1 <= candle.high).then_some(Ohlc { time, candle })
+}
+
+/// Stock index and price of one `--stdin` line, if it names a known symbol.
+fn parse_stdin_line(line: &str, symbols: &[String]) -> Option<(usize, f64)> {
+    let line = line.trim();
+    let (symbol, price) = if line.starts_with('{') {
+        let tick: StdinTick = serde_json::from_str(line).ok()?;
+        (tick.symbol, tick.price)
+    } else {
+        let (symbol, price) = line.split_once(char::is_whitespace)?;
+        (symbol.to_string(), price.trim().parse().ok()?)
+    };
+    let id = symbols.iter().position(|s| *s == symbol)?;
+    Some((id, price))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_prints_are_caught() {
+        assert_eq!(bad_print(f64::NAN, true), Some("non-finite"));
+        assert_eq!(bad_print(f64::INFINITY, false), Some("non-finite"));
+        assert_eq!(bad_print(-1.0, false), Some("negative"));
+        assert_eq!(bad_print(-1.0, true), None);
+        assert_eq!(bad_print(0.0, false), None);
+    }
+
+    #[test]
+    fn stdin_replay_skips_to_the_newest_line() {
+        let (tx, rx) = mpsc::channel();
+        let mut replay = Replay {
+            queues: vec![VecDeque::new(); 2],
+            bars: vec![],
+            live: Some(rx),
+        };
+        for price in [101.0, 102.0, 103.0] {
+            tx.send((0, price)).unwrap();
+        }
+        tx.send((1, 50.0)).unwrap();
+        assert_eq!(replay.next_price(0), Some(103.0));
+        assert_eq!(replay.next_price(0), None);
+        assert_eq!(replay.next_price(1), Some(50.0));
+    }
+
+    #[test]
+    fn jumps_arrive_at_their_rate() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let jumps = Jumps {
+            rate: 2.0,
+            size: 0.05,
+        };
+        let dt = Duration::from_millis(100);
+        let hits: Vec<f64> = (0..10_000)
+            .filter_map(|_| jumps.sample(&mut rng, 100.0, dt))
+            .collect();
+        // 1 - e^-0.2 of ticks, about 1813 in 10 000
+        assert!((1700..1930).contains(&hits.len()), "{}", hits.len());
+        assert!(hits.iter().all(|j| j.abs() <= 10.0));
+        assert!(hits.iter().any(|j| *j > 0.0) && hits.iter().any(|j| *j < 0.0));
+    }
+
+    #[test]
+    fn an_injected_freeze_runs_its_length() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut faults = FaultInjector::new(1.0, 3);
+        let fault = std::iter::repeat_with(|| faults.next(&mut rng, 2, false))
+            .find(|f| *f == Some(Fault::Frozen));
+        assert!(fault.is_some());
+        assert!(faults.freezing(2));
+        assert!(faults.freezing(2));
+        assert!(!faults.freezing(2));
+        assert!(!faults.freezing(0));
+    }
+
+    #[test]
+    fn stocks_allowed_below_zero_get_no_negative_faults() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut faults = FaultInjector::new(1.0, 3);
+        assert!((0..1000).all(|_| faults.next(&mut rng, 0, true) != Some(Fault::Negative)));
+    }
+}
@@ -0,0 +1,44 @@
+//! Plain-prose description of the market state for `--accessible`, where a
+//! screen reader reads lines instead of anyone looking at charts.
+
+use crate::indicators;
+use crate::MarketData;
+
+/// Relative change in volatility between the older and newer half of the
+/// history below which it is reported as steady.
+const VOL_TREND_BAND: f64 = 0.1;
+
+/// e.g. "AAPL up 1.2% to 101.20, volatility rising".
+pub fn summarize(md: &MarketData, lookback: usize) -> String {
+    let price = md.history.last().copied().unwrap_or_default();
+    let change = indicators::pct_change(&md.history, lookback);
+    let direction = if change > 0.0 {
+        format!("up {:.1}% to", change)
+    } else if change < 0.0 {
+        format!("down {:.1}% to", -change)
+    } else {
+        "unchanged at".to_string()
+    };
+    format!(
+        "{} {} {:.2}, volatility {}",
+        md.symbol,
+        direction,
+        price,
+        volatility_trend(&md.history)
+    )
+}
+
+/// Compares the spread of returns in the newer half of the window with the
+/// older half.
+fn volatility_trend(history: &[f64]) -> &'static str {
+    let returns = indicators::returns(history);
+    let (older, newer) = returns.split_at(returns.len() / 2);
+    let (before, after) = (indicators::stddev(older), indicators::stddev(newer));
+    if after > before * (1.0 + VOL_TREND_BAND) {
+        "rising"
+    } else if after < before * (1.0 - VOL_TREND_BAND) {
+        "falling"
+    } else {
+        "steady"
+    }
+}
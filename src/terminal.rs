@@ -0,0 +1,110 @@
+use std::env;
+use std::io::{self, IsTerminal, Stdout};
+use std::ops::{Deref, DerefMut};
+
+use crossterm::{
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend, TestBackend},
+    Terminal,
+};
+
+/// Why the TUI cannot run here, if it cannot. Frames go out on stdout,
+/// which has to be a terminal that can do more than print lines; drawing
+/// into anything else (a pipe, a file, `TERM=dumb`) leaves garbage instead
+/// of an error. Stdin may be piped (`--stdin`): crossterm then reads keys
+/// from `/dev/tty`.
+pub fn unsupported() -> Option<&'static str> {
+    if !io::stdout().is_terminal() {
+        Some("stdout is not a terminal (is the output piped or redirected?)")
+    } else if env::var("TERM").is_ok_and(|term| term == "dumb") {
+        Some("TERM=dumb cannot draw a full-screen interface")
+    } else {
+        None
+    }
+}
+
+/// What a ratatui backend needs done to the real terminal around a run.
+/// The drawing itself is backend-agnostic already; only this differs.
+pub trait TerminalSetup: Backend {
+    fn setup(&mut self) -> io::Result<()>;
+    fn restore(&mut self) -> io::Result<()>;
+}
+
+impl TerminalSetup for CrosstermBackend<Stdout> {
+    fn setup(&mut self) -> io::Result<()> {
+        enable_raw_mode()?;
+        self.execute(EnterAlternateScreen)?;
+        Ok(())
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        disable_raw_mode()?;
+        self.execute(LeaveAlternateScreen)?;
+        Ok(())
+    }
+}
+
+/// In-memory screen: nothing to set up, which is what lets tests drive a
+/// `TerminalGuard` without a TTY.
+impl TerminalSetup for TestBackend {
+    fn setup(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Owns the terminal while the TUI is running and restores it on drop, so
+/// early returns, `?` and panics in the main loop all leave a usable shell.
+pub struct TerminalGuard<B: TerminalSetup> {
+    terminal: Terminal<B>,
+}
+
+impl<B: TerminalSetup> TerminalGuard<B> {
+    pub fn new(mut backend: B) -> io::Result<Self> {
+        backend.setup()?;
+        let terminal = Terminal::new(backend)?;
+        Ok(Self { terminal })
+    }
+}
+
+impl<B: TerminalSetup> Drop for TerminalGuard<B> {
+    fn drop(&mut self) {
+        let _ = self.terminal.backend_mut().restore();
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+impl<B: TerminalSetup> Deref for TerminalGuard<B> {
+    type Target = Terminal<B>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl<B: TerminalSetup> DerefMut for TerminalGuard<B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::widgets::Paragraph;
+
+    #[test]
+    fn guard_draws_on_a_test_backend() {
+        let mut terminal = TerminalGuard::new(TestBackend::new(10, 1)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(Paragraph::new("hello"), f.area()))
+            .unwrap();
+        terminal.backend().assert_buffer_lines(["hello     "]);
+    }
+}
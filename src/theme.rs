@@ -0,0 +1,142 @@
+use clap::ValueEnum;
+use ratatui::style::Color;
+
+/// Terminal background the colors are picked for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Background {
+    Dark,
+    Light,
+}
+
+/// Every color the UI draws with, chosen once at startup.
+#[derive(Clone)]
+pub struct Theme {
+    series: Vec<Color>,
+    /// Secondary text and reference lines.
+    pub muted: Color,
+    /// Positive and negative changes.
+    pub gain: Color,
+    pub loss: Color,
+    /// Heat gradient ends at -1 and +1, and its neutral middle.
+    heat_low: Rgb,
+    heat_high: Rgb,
+    heat_mid: Rgb,
+    /// Plain text, the neutral end of text shaded by magnitude.
+    text: Rgb,
+    background: Background,
+}
+
+pub type Rgb = [u8; 3];
+
+impl Theme {
+    /// `heat` overrides the gradient ends (`--heat-colors`).
+    pub fn new(background: Background, heat: Option<(Rgb, Rgb)>) -> Self {
+        let mut theme = match background {
+            Background::Dark => Self {
+                series: vec![Color::Red, Color::Green, Color::Yellow],
+                muted: Color::DarkGray,
+                gain: Color::Green,
+                loss: Color::Red,
+                heat_low: [180, 0, 0],
+                heat_high: [0, 180, 0],
+                heat_mid: [30, 30, 30],
+                text: [255, 255, 255],
+                background,
+            },
+            // The bright ANSI defaults wash out on white, so use deeper shades
+            Background::Light => Self {
+                series: vec![
+                    Color::Rgb(170, 0, 0),
+                    Color::Rgb(0, 120, 0),
+                    Color::Rgb(150, 100, 0),
+                ],
+                muted: Color::Rgb(110, 110, 110),
+                gain: Color::Rgb(0, 120, 0),
+                loss: Color::Rgb(170, 0, 0),
+                heat_low: [255, 115, 115],
+                heat_high: [115, 255, 115],
+                heat_mid: [235, 235, 235],
+                text: [0, 0, 0],
+                background,
+            },
+        };
+        if let Some((low, high)) = heat {
+            theme.heat_low = low;
+            theme.heat_high = high;
+        }
+        theme
+    }
+
+    /// Color of stock `i`; the palette repeats when there are more stocks
+    /// than colors.
+    pub fn series(&self, i: usize) -> Color {
+        self.series[i % self.series.len()]
+    }
+
+    /// Green for a rise, red for a fall, muted when flat.
+    pub fn change(&self, delta: f64) -> Color {
+        if delta > 0.0 {
+            self.gain
+        } else if delta < 0.0 {
+            self.loss
+        } else {
+            self.muted
+        }
+    }
+
+    /// Color for a normalized value `t` in -1..=1 (clamped), blended
+    /// smoothly from the neutral middle at 0 towards the low or high end.
+    pub fn heat_color(&self, t: f64) -> Color {
+        self.blend(self.heat_mid, t)
+    }
+
+    /// Text color that reads on `heat_color(t)`: black on light cells,
+    /// white on dark ones, whatever the background or `--heat-colors`.
+    pub fn heat_text(&self, t: f64) -> Color {
+        let [r, g, b] = self.blend_rgb(self.heat_mid, t).map(f64::from);
+        // Rec. 709 luma
+        if 0.2126 * r + 0.7152 * g + 0.0722 * b > 140.0 {
+            Color::Black
+        } else {
+            Color::White
+        }
+    }
+
+    /// Text color for a z-score: plain near 0, shading into the heat ends
+    /// and saturating at ±3 standard deviations.
+    pub fn zscore(&self, z: f64) -> Color {
+        self.blend(self.text, z / 3.0)
+    }
+
+    fn blend(&self, mid: Rgb, t: f64) -> Color {
+        let [r, g, b] = self.blend_rgb(mid, t);
+        Color::Rgb(r, g, b)
+    }
+
+    fn blend_rgb(&self, mid: Rgb, t: f64) -> Rgb {
+        let t = t.clamp(-1.0, 1.0);
+        let end = if t < 0.0 {
+            self.heat_low
+        } else {
+            self.heat_high
+        };
+        std::array::from_fn(|i| lerp(mid[i], end[i], t.abs()))
+    }
+
+    /// Background tint for an alarm of strength `t` in 0..=1, from barely
+    /// visible to full red.
+    pub fn alarm_bg(&self, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        match self.background {
+            Background::Dark => Color::Rgb((60.0 + 140.0 * t) as u8, 0, 0),
+            Background::Light => {
+                let gb = (235.0 - 120.0 * t) as u8;
+                Color::Rgb(255, gb, gb)
+            }
+        }
+    }
+}
+
+fn lerp(from: u8, to: u8, t: f64) -> u8 {
+    (from as f64 + (to as f64 - from as f64) * t).round() as u8
+}
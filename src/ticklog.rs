@@ -0,0 +1,130 @@
+//! The tick log (`stock_data.txt`) the Postgres flush picks up, written on
+//! its own thread so the updater never waits on disk, with a configurable
+//! flush policy (`--tick-flush`).
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::error;
+
+/// When buffered ticks are written out. Until then they exist only in
+/// memory, so a crash loses them; every write costs an append to the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Once this many ticks are buffered (`ticks:N`; `ticks:1` is every tick).
+    Ticks(u32),
+    /// Once the oldest buffered tick is this old (`ms:T`).
+    Every(Duration),
+    /// Only when the app exits (`exit`).
+    OnExit,
+}
+
+/// `ticks:N`, `ms:T` or `exit`.
+pub fn parse_flush_policy(s: &str) -> Result<FlushPolicy, String> {
+    if s == "exit" {
+        return Ok(FlushPolicy::OnExit);
+    }
+    let usage = || format!("{}: expected ticks:N, ms:T or exit", s);
+    let (kind, n) = s.split_once(':').ok_or_else(usage)?;
+    let n: u32 = n.parse().map_err(|_| usage())?;
+    if n == 0 {
+        return Err(format!("{}: must be at least 1", s));
+    }
+    match kind {
+        "ticks" => Ok(FlushPolicy::Ticks(n)),
+        "ms" => Ok(FlushPolicy::Every(Duration::from_millis(n.into()))),
+        _ => Err(usage()),
+    }
+}
+
+enum Message {
+    Tick(i32, f64),
+    Close,
+}
+
+/// Sending side of the logger; cheap to clone into the updater.
+#[derive(Clone)]
+pub struct TickLog {
+    tx: Sender<Message>,
+}
+
+/// Keeps the writer thread joinable, so exiting can wait for the last flush.
+pub struct TickLogWriter {
+    tx: Sender<Message>,
+    thread: JoinHandle<()>,
+}
+
+impl TickLog {
+    pub fn log(&self, stock_id: i32, price: f64) {
+        let _ = self.tx.send(Message::Tick(stock_id, price));
+    }
+}
+
+impl TickLogWriter {
+    /// Writes out whatever is still buffered and stops the thread.
+    pub fn close(self) {
+        let _ = self.tx.send(Message::Close);
+        let _ = self.thread.join();
+    }
+}
+
+pub fn spawn(path: PathBuf, policy: FlushPolicy) -> (TickLog, TickLogWriter) {
+    let (tx, rx) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        let mut pending: Vec<u8> = vec![];
+        let mut buffered = 0;
+        // When the oldest buffered tick arrived
+        let mut since: Option<Instant> = None;
+        let flush = |pending: &mut Vec<u8>| -> io::Result<()> {
+            if !pending.is_empty() {
+                let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+                file.write_all(pending)?;
+                pending.clear();
+            }
+            Ok(())
+        };
+        loop {
+            let message = match (policy, since) {
+                (FlushPolicy::Every(every), Some(at)) => {
+                    match rx.recv_timeout(every.saturating_sub(at.elapsed())) {
+                        Ok(message) => Some(message),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => Some(Message::Close),
+                    }
+                }
+                _ => Some(rx.recv().unwrap_or(Message::Close)),
+            };
+            let due = match message {
+                Some(Message::Tick(stock_id, price)) => {
+                    let _ = writeln!(pending, "{},{}", stock_id, price);
+                    buffered += 1;
+                    since.get_or_insert_with(Instant::now);
+                    match policy {
+                        FlushPolicy::Ticks(n) => buffered >= n,
+                        FlushPolicy::Every(every) => since.is_some_and(|at| at.elapsed() >= every),
+                        FlushPolicy::OnExit => false,
+                    }
+                }
+                Some(Message::Close) => {
+                    if let Err(e) = flush(&mut pending) {
+                        error!("Tick log flush failed: {:?}", e);
+                    }
+                    return;
+                }
+                None => true,
+            };
+            if due {
+                if let Err(e) = flush(&mut pending) {
+                    error!("Tick log flush failed: {:?}", e);
+                }
+                buffered = 0;
+                since = None;
+            }
+        }
+    });
+    (TickLog { tx: tx.clone() }, TickLogWriter { tx, thread })
+}
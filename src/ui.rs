@@ -0,0 +1,1660 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    symbols::{self, border},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::alerts::AlertLog;
+use crate::app::{App, IndicatorPage, Mode, SnapshotPair, TimeUnit, View, Zoom, SHORT_WINDOW_LEN};
+use crate::chart::{
+    faded_series, tail, to_points, to_points_ending_at, to_points_timed, y_bounds, ChartSpec,
+    Series,
+};
+use crate::indicators::{self, Candle, ReturnKind};
+use crate::keys;
+use crate::orders::{self, Blotter, Fill, Side};
+use crate::panes::{Content, Pane};
+use crate::sim::SimControl;
+use crate::{MarketData, UiData, MOVING_AVG_LEN};
+
+/// Most recent trades marked on a stock's chart.
+const TRADE_MARKERS: usize = 5;
+/// Tallest the pointers panel grows before its lines are cut off.
+const MAX_POINTERS_HEIGHT: u16 = 14;
+/// Rows of the `t` raw tick table.
+const RAW_TICKS: usize = 10;
+/// Smallest (width, height) of a candle grid cell; fewer fit, more pages.
+const CANDLE_CELL: (u16, u16) = (36, 10);
+
+pub fn draw(
+    f: &mut Frame,
+    app: &App,
+    sim: &SimControl,
+    alerts: &AlertLog,
+    orders: &Blotter,
+    md_vec: &[MarketData],
+    ui_vec: &[UiData],
+) {
+    // Two lines per stock plus borders, capped so many stocks still leave room
+    // for the charts. A zoomed chart takes the whole screen, pointers included.
+    let pointers_height = if app.zoom == Zoom::Split {
+        // The table has one row per stock under its header
+        let rows = if app.pointer_table {
+            md_vec.len() + 1
+        } else {
+            md_vec.len() * 2
+        };
+        (rows as u16 + 2).min(MAX_POINTERS_HEIGHT)
+    } else {
+        0
+    };
+    // The orders strip only appears once something has been traded
+    let orders_height = if orders.is_empty() { 0 } else { 3 };
+    let crosshair_height = if app.crosshair.is_some() { 3 } else { 0 };
+    let snapshot_height = if app.snapshot_pair().is_some() { 3 } else { 0 };
+    let pair_height = if app.pair.is_some() { 3 } else { 0 };
+    // Header row and borders around the fixed number of rows
+    let ticks_height = if app.show_ticks {
+        RAW_TICKS as u16 + 3
+    } else {
+        0
+    };
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(pointers_height),
+            Constraint::Min(10),
+            Constraint::Length(crosshair_height),
+            Constraint::Length(snapshot_height),
+            Constraint::Length(pair_height),
+            Constraint::Length(ticks_height),
+            Constraint::Length(orders_height),
+            Constraint::Length(app.ticker.map_or(0, |_| 1)),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
+
+    if app.zoom == Zoom::Split {
+        let top_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+            .split(main_chunks[0]);
+        draw_pointers(f, app, md_vec, ui_vec, top_chunks[0]);
+        draw_alerts(f, app, alerts, top_chunks[1]);
+    }
+
+    match app.view {
+        View::Overview => match &app.panes {
+            Some(panes) => draw_panes(f, app, panes, orders, md_vec, ui_vec, main_chunks[1]),
+            None => draw_overview(f, app, orders, md_vec, ui_vec, main_chunks[1]),
+        },
+        View::Detail => draw_detail(f, app, orders, &md_vec[app.selected], main_chunks[1]),
+        View::CandleGrid => draw_candle_grid(f, app, md_vec, main_chunks[1]),
+    }
+
+    if let Some(age) = app.crosshair {
+        draw_crosshair_readout(f, app, age, md_vec, main_chunks[2]);
+        if app.inspect {
+            draw_inspect(f, app, age, orders, &md_vec[app.selected], main_chunks[1]);
+        }
+    }
+    if let Some(pair) = app.snapshot_pair() {
+        draw_snapshot_diff(f, app, pair, md_vec, main_chunks[3]);
+    }
+    if let Some(pair) = app.pair {
+        draw_pair_spread(f, app, pair, md_vec, main_chunks[4]);
+    }
+    if app.show_ticks {
+        draw_raw_ticks(f, app, md_vec, main_chunks[5]);
+    }
+    if !orders.is_empty() {
+        draw_orders(f, app, orders, md_vec, main_chunks[6]);
+    }
+    let density = density_hint(app, main_chunks[1]);
+    if let Some(offset) = app.ticker {
+        draw_ticker(f, app, offset, md_vec, main_chunks[7]);
+    }
+    draw_footer(f, app, sim, md_vec, density, main_chunks[8]);
+
+    if let Some(matrix) = &app.correlation {
+        draw_correlation(f, app, matrix, md_vec, main_chunks[1]);
+    }
+    if app.show_help {
+        draw_help(f, app);
+    }
+    if app.mode == Mode::ConfirmingQuit {
+        draw_popup(f, app, "Quit? (y/n)");
+    }
+}
+
+// -------------------- Panels --------------------
+
+fn draw_pointers(f: &mut Frame, app: &App, md_vec: &[MarketData], ui_vec: &[UiData], area: Rect) {
+    if app.pointer_table {
+        draw_pointer_table(f, app, md_vec, ui_vec, area);
+        return;
+    }
+    let marker = |i: usize| if i == app.selected { '>' } else { ' ' };
+
+    let histories: Vec<&[f64]> = md_vec.iter().map(|md| md.history.as_slice()).collect();
+    let index_returns = app.return_kind.of(&indicators::basket_index(&histories));
+
+    let dp = app.decimals;
+    let mut lines = vec![];
+    for md in md_vec.iter() {
+        let val = *md.price.read().unwrap();
+        let mut spans = vec![Span::raw(format!(
+            "{} Backend {} -> ptr: {:p}, value: {:>8.*}",
+            marker(md.count),
+            md.symbol,
+            Arc::as_ptr(&md.price),
+            dp.price,
+            val,
+        ))];
+        spans.extend(stock_tags(app, md));
+        for column in page_columns(app.indicator_page) {
+            spans.push(Span::raw(", "));
+            spans.push(backend_column(app, *column, md, &index_returns));
+        }
+        if app.indicator_page == IndicatorPage::All {
+            spans.push(Span::raw(custom_columns(app, md)));
+        }
+        lines.push(Line::from(spans));
+    }
+    for ui in ui_vec.iter() {
+        let md = &md_vec[ui.count];
+        let delta = *md.price.read().unwrap() - *ui.value;
+        let median = indicators::rolling_median(tail(&md.history, MOVING_AVG_LEN));
+        let line = Line::from(format!(
+            "{} Frontend {} -> ptr: {:p}, {}: {:>8.*}, med: {:>8.*}, Δ: {:>+7.*}, every: {:>9}, stale: {:>9}",
+            marker(ui.count),
+            md.symbol,
+            Arc::as_ptr(&ui.value),
+            app.frontend_model.label(),
+            dp.avg,
+            *ui.value,
+            dp.avg,
+            median,
+            dp.price,
+            delta,
+            format_duration(ui.interval, app.time_unit),
+            format_duration(ui.last_update.elapsed(), app.time_unit)
+        ));
+        lines.push(match diff_heat(delta, app.diff_threshold) {
+            Some(t) => line.style(Style::default().bg(app.theme.alarm_bg(t))),
+            None => line,
+        });
+    }
+    let mut title = match app.indicator_page {
+        IndicatorPage::All => "Pointers".to_string(),
+        page => format!("Pointers · {}", page.label()),
+    };
+    if app.return_kind == ReturnKind::Log {
+        title.push_str(" · log returns");
+    }
+    f.render_widget(Paragraph::new(lines).block(panel(title, app)), area);
+}
+
+/// `HIDDEN` / `FROZEN` and `ALERT` tags after a stock's price, each with
+/// its leading space.
+fn stock_tags(app: &App, md: &MarketData) -> Vec<Span<'static>> {
+    let mut tags = vec![];
+    if md.hidden {
+        tags.push(Span::styled(
+            " HIDDEN",
+            Style::default()
+                .fg(app.theme.muted)
+                .add_modifier(Modifier::BOLD),
+        ));
+    } else if md.frozen(app.frozen_ticks) {
+        // A stuck feed still ticks on time, so staleness alone misses it
+        tags.push(Span::styled(
+            " FROZEN",
+            Style::default()
+                .fg(app.theme.loss)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(level) = md.levels.flashing() {
+        tags.push(Span::styled(
+            format!(" ALERT {:.*}", app.decimals.price, level),
+            Style::default()
+                .fg(app.theme.loss)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        ));
+    }
+    tags
+}
+
+/// `--table`: the pointers panel as one aligned row per stock instead of
+/// free-form lines, the symbol in the stock's series color. Δ is the price
+/// minus the frontend value, tinted like the frontend lines past the alarm.
+fn draw_pointer_table(
+    f: &mut Frame,
+    app: &App,
+    md_vec: &[MarketData],
+    ui_vec: &[UiData],
+    area: Rect,
+) {
+    let dp = app.decimals;
+    let unit = app.time_unit;
+    // Right-aligned, so digits line up however wide the values get
+    let number = |text: String| Cell::from(Line::from(text).alignment(Alignment::Right));
+    let rows = ui_vec.iter().map(|ui| {
+        let md = &md_vec[ui.count];
+        let marker = if md.count == app.selected { "> " } else { "  " };
+        let symbol = Line::from(vec![
+            Span::raw(marker),
+            Span::styled(
+                md.symbol.clone(),
+                Style::default().fg(app.theme.series(md.count)),
+            ),
+        ]);
+        let price = *md.price.read().unwrap();
+        let delta = price - *ui.value;
+        let delta_style = diff_heat(delta, app.diff_threshold).map_or(Style::default(), |t| {
+            Style::default().bg(app.theme.alarm_bg(t))
+        });
+        let change = indicators::pct_change(&md.history, app.change_lookback);
+        let latency = if app.smooth_latency {
+            format!("~{}", format_duration(md.latency.smoothed, unit))
+        } else {
+            format_duration(md.latency.last, unit)
+        };
+        Row::new([
+            Cell::from(symbol),
+            number(format!("{:.*}", dp.price, price)),
+            number(format!("{:.*}", dp.avg, *ui.value)),
+            Cell::from(
+                Line::from(format!("{:+.*}", dp.price, delta))
+                    .alignment(Alignment::Right)
+                    .style(delta_style),
+            ),
+            Cell::from(
+                Line::from(format!("{:+.*}%", dp.percent, change))
+                    .alignment(Alignment::Right)
+                    .style(Style::default().fg(app.theme.change(change))),
+            ),
+            number(format!(
+                "{:.*}%",
+                dp.percent,
+                indicators::stddev(&app.return_kind.of(&md.history)) * 100.0
+            )),
+            number(latency),
+            Cell::from(Line::from(stock_tags(app, md))),
+        ])
+    });
+    let symbol_width = md_vec
+        .iter()
+        .map(|md| md.symbol.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let mut header: Vec<Cell> = ["Price", "Avg", "Δ", "%Chg", "σ", "Latency"]
+        .map(|h| Cell::from(Line::from(h).alignment(Alignment::Right)))
+        .into();
+    header.insert(0, Cell::from("  Symbol"));
+    let header = Row::new(header).style(Style::default().add_modifier(Modifier::BOLD));
+    let title = match app.return_kind {
+        ReturnKind::Log => "Pointers · log returns",
+        ReturnKind::Simple => "Pointers",
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(symbol_width.max("  Symbol".len()) as u16),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(11),
+            // HIDDEN / FROZEN / ALERT, in whatever room is left
+            Constraint::Min(0),
+        ],
+    )
+    .header(header)
+    .column_spacing(1)
+    .block(panel(title, app));
+    f.render_widget(table, area);
+}
+
+/// A value on a backend pointer line; which ones show depends on the page.
+#[derive(Clone, Copy)]
+enum Column {
+    Change,
+    Imbalance,
+    Age,
+    Beta,
+    Latency,
+    Rsi,
+    Ewma,
+    Average,
+    ZScore,
+    Acceleration,
+    Volatility,
+    Skew,
+    Kurtosis,
+    Drawdown,
+    Size,
+}
+
+fn page_columns(page: IndicatorPage) -> &'static [Column] {
+    use Column::*;
+    match page {
+        IndicatorPage::All => &[Change, Imbalance, Age, Beta, Latency, Size],
+        IndicatorPage::Basic => &[Change, Average, Age, Latency],
+        IndicatorPage::Momentum => &[Change, Rsi, Ewma, Average, ZScore, Acceleration],
+        IndicatorPage::Volatility => &[Volatility, ZScore, Skew, Kurtosis, Imbalance],
+        IndicatorPage::Risk => &[Beta, Drawdown, Volatility, Size],
+    }
+}
+
+fn backend_column(
+    app: &App,
+    column: Column,
+    md: &MarketData,
+    index_returns: &[f64],
+) -> Span<'static> {
+    let dp = app.decimals;
+    let unit = app.time_unit;
+    let returns = || app.return_kind.of(&md.history);
+    match column {
+        Column::Change => {
+            let change = indicators::pct_change(&md.history, app.change_lookback);
+            Span::styled(
+                format!("%Δ: {:>+6.*}%", dp.percent, change),
+                Style::default().fg(app.theme.change(change)),
+            )
+        }
+        Column::Imbalance => {
+            let imbalance = md.book.imbalance() * 100.0;
+            Span::styled(
+                format!("imb: {:>+4.*}%", dp.percent, imbalance),
+                Style::default().fg(app.theme.change(imbalance)),
+            )
+        }
+        Column::Age => Span::raw(format!(
+            "age: {:>9}",
+            format_duration(md.last_update.elapsed(), unit)
+        )),
+        Column::Beta => Span::raw(format!(
+            "β: {:>5.*}",
+            dp.indicator,
+            indicators::beta(&returns(), index_returns)
+        )),
+        Column::Latency => {
+            let latency = if app.smooth_latency {
+                format!("~{}", format_duration(md.latency.smoothed, unit))
+            } else {
+                format_duration(md.latency.last, unit)
+            };
+            Span::raw(format!(
+                "lat: {} ({}/{}/{})",
+                latency,
+                format_duration(md.latency.min.unwrap_or_default(), unit),
+                format_duration(md.latency.mean(), unit),
+                format_duration(md.latency.max, unit)
+            ))
+        }
+        Column::Rsi => Span::raw(format!(
+            "RSI: {:>5}",
+            or_dashes(
+                indicators::rsi(md.samples(), indicators::RSI_PERIOD),
+                dp.percent
+            )
+        )),
+        Column::Ewma => Span::raw(format!(
+            "ewma: {:>8}",
+            or_dashes(
+                indicators::ewma(md.samples(), indicators::EWMA_ALPHA),
+                dp.avg
+            )
+        )),
+        Column::Average => {
+            // `times` lines up with `history` from the end
+            let samples = md.samples();
+            let times = &md.times[md.times.len() - samples.len()..];
+            let timed: Vec<(Instant, f64)> =
+                times.iter().copied().zip(samples.iter().copied()).collect();
+            Span::raw(format!(
+                "avg: {:>8.*}, twap: {:>8.*}",
+                dp.avg,
+                indicators::mean(samples),
+                dp.avg,
+                indicators::twap(&timed)
+            ))
+        }
+        Column::ZScore => {
+            let z = indicators::zscore(md.samples());
+            Span::styled(
+                format!("z: {:>+5.*}", dp.indicator, z),
+                Style::default().fg(app.theme.zscore(z)),
+            )
+        }
+        Column::Acceleration => Span::raw(format!(
+            "accel: {:+.*e}",
+            dp.indicator,
+            indicators::acceleration(&md.history)
+        )),
+        Column::Volatility => Span::raw(format!(
+            "σ: {:.*}%",
+            dp.percent,
+            indicators::stddev(&returns()) * 100.0
+        )),
+        Column::Skew => Span::raw(format!(
+            "skew: {:>+5.*}",
+            dp.indicator,
+            indicators::skewness(&returns())
+        )),
+        Column::Kurtosis => Span::raw(format!(
+            "kurt: {:>+5.*}",
+            dp.indicator,
+            indicators::kurtosis(&returns())
+        )),
+        Column::Size => Span::raw(format!(
+            "size: {:>4}",
+            orders::risk_size(md, app.target_risk, app.return_kind)
+                .map_or("--".to_string(), |n| n.to_string())
+        )),
+        Column::Drawdown => Span::raw(format!(
+            "max dd: {:.*}%",
+            dp.percent,
+            indicators::max_drawdown(&md.history) * 100.0
+        )),
+    }
+}
+
+/// `, name: value` for every registered custom indicator.
+fn custom_columns(app: &App, md: &MarketData) -> String {
+    app.custom_indicators
+        .iter()
+        .map(|(name, indicator)| {
+            format!(
+                ", {}: {:.*}",
+                name,
+                app.decimals.indicator,
+                indicator(md.samples())
+            )
+        })
+        .collect()
+}
+
+/// How far past the threshold the divergence is, saturating at twice the
+/// threshold; `None` while the frontend is still within tolerance.
+fn diff_heat(delta: f64, threshold: f64) -> Option<f64> {
+    let excess = delta.abs() - threshold;
+    (excess > 0.0).then(|| (excess / threshold).min(1.0))
+}
+
+/// Every stock's price `age` ticks back, under the crosshair.
+fn draw_crosshair_readout(f: &mut Frame, app: &App, age: usize, md_vec: &[MarketData], area: Rect) {
+    let prices: Vec<String> = md_vec
+        .iter()
+        .map(|md| {
+            let price = md.history.len().checked_sub(age + 1).map(|i| md.history[i]);
+            format!("{} {}", md.symbol, or_dashes(price, app.decimals.price))
+        })
+        .collect();
+    let title = format!("Crosshair · {} ticks back", age);
+    f.render_widget(
+        Paragraph::new(prices.join(" · ")).block(panel(title, app)),
+        area,
+    );
+}
+
+/// The selected stock's sample under the crosshair as stored: the price to
+/// the last bit, when it was taken and what filled on that tick, in a box
+/// at the top right of the charts.
+fn draw_inspect(
+    f: &mut Frame,
+    app: &App,
+    age: usize,
+    orders: &Blotter,
+    md: &MarketData,
+    area: Rect,
+) {
+    let tick = md.ticks.checked_sub(age as u64);
+    let index = md.history.len().checked_sub(age + 1);
+    let lines: Vec<String> = match (tick, index) {
+        (Some(tick), Some(i)) => {
+            let price = md.history[i];
+            let at = md.times[i];
+            let mut lines = vec![
+                format!("price   {}", price),
+                format!("bits    {:#018x}", price.to_bits()),
+                format!(
+                    "change  {}",
+                    i.checked_sub(1)
+                        .map_or("--".to_string(), |p| format!("{:+}", price - md.history[p]))
+                ),
+                format!(
+                    "at      {:?} after start, {:?} ago",
+                    at.saturating_duration_since(app.start),
+                    at.elapsed()
+                ),
+            ];
+            if md.closed.contains(tick) {
+                lines.push("session closed".to_string());
+            }
+            let fills: Vec<&Fill> = orders
+                .recent_fills_of(md.count, usize::MAX)
+                .filter(|fill| fill.tick == tick)
+                .collect();
+            if fills.is_empty() {
+                lines.push("fills   none".to_string());
+            }
+            for fill in fills {
+                lines.push(format!(
+                    "fill    {} {} @ {} (quoted {})",
+                    fill.side, fill.qty, fill.price, fill.quoted
+                ));
+            }
+            lines
+        }
+        _ => vec!["no tick that far back".to_string()],
+    };
+    let title = match tick {
+        Some(tick) => format!("Inspect · {} tick {}", md.symbol, tick),
+        None => format!("Inspect · {}", md.symbol),
+    };
+    let width = lines
+        .iter()
+        .chain([&title])
+        .map(|l| display_width(l))
+        .max()
+        .unwrap_or(0) as u16
+        + 4;
+    let width = width.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let rect = Rect {
+        x: area.right() - width,
+        y: area.y,
+        width,
+        height,
+    };
+    let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(text).block(panel(title, app)), rect);
+}
+
+/// The last `RAW_TICKS` prices of every stock exactly as stored, newest on
+/// top. The start price is left out, so a fresh stock shows dashes.
+fn draw_raw_ticks(f: &mut Frame, app: &App, md_vec: &[MarketData], area: Rect) {
+    // Room for a sign, five integer digits and the point around the decimals
+    let cell = app.decimals.price + 9;
+    let mut header = format!("{:>5}", "");
+    for md in md_vec {
+        header.push_str(&format!("{:>cell$}", md.symbol));
+    }
+    let mut lines = vec![Line::from(header).style(Style::default().fg(app.theme.muted))];
+    for age in 0..RAW_TICKS {
+        let mut row = if age == 0 {
+            format!("{:>5}", "now")
+        } else {
+            format!("{:>5}", -(age as i64))
+        };
+        for md in md_vec {
+            let samples = md.samples();
+            let price = samples.len().checked_sub(age + 1).map(|i| samples[i]);
+            row.push_str(&format!("{:>cell$}", or_dashes(price, app.decimals.price)));
+        }
+        lines.push(Line::from(row));
+    }
+    f.render_widget(Paragraph::new(lines).block(panel("Raw ticks", app)), area);
+}
+
+/// Per-stock move between the two latest `P` snapshots, coloured by sign.
+fn draw_snapshot_diff(
+    f: &mut Frame,
+    app: &App,
+    ((from, older), (to, newer)): SnapshotPair,
+    md_vec: &[MarketData],
+    area: Rect,
+) {
+    let dp = app.decimals;
+    let mut spans = vec![];
+    for ((md, before), after) in md_vec.iter().zip(&older.prices).zip(&newer.prices) {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" · "));
+        }
+        let change = after - before;
+        let pct = if *before == 0.0 {
+            0.0
+        } else {
+            change / before * 100.0
+        };
+        spans.push(Span::styled(
+            format!(
+                "{} {:+.*} ({:+.*}%)",
+                md.symbol, dp.price, change, dp.percent, pct
+            ),
+            Style::default().fg(app.theme.change(change)),
+        ));
+    }
+    let title = format!(
+        "Snapshot {} → {} · {:.1}s apart",
+        from,
+        to,
+        newer.at.duration_since(older.at).as_secs_f64()
+    );
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).block(panel(title, app)),
+        area,
+    );
+}
+
+/// One-line pair strip (`--pair`): the raw spread, the spread hedged by
+/// the pair's beta over the backend window, and that hedge ratio.
+fn draw_pair_spread(
+    f: &mut Frame,
+    app: &App,
+    (a, b): (usize, usize),
+    md_vec: &[MarketData],
+    area: Rect,
+) {
+    let (Some(md_a), Some(md_b)) = (md_vec.get(a), md_vec.get(b)) else {
+        let missing = if a < md_vec.len() { b } else { a };
+        let text = format!(
+            "no stock {} (0–{})",
+            missing,
+            md_vec.len().saturating_sub(1)
+        );
+        f.render_widget(Paragraph::new(text).block(panel("Pair", app)), area);
+        return;
+    };
+    let window = app.plot_points;
+    let dp = app.decimals;
+    let title = format!(
+        "Pair {}/{} · β over {} ticks",
+        md_a.symbol, md_b.symbol, window
+    );
+    let text =
+        match indicators::hedged_spread(tail(&md_a.history, window), tail(&md_b.history, window)) {
+            Some(spread) => Line::from(vec![
+                Span::raw(format!(
+                    "{} − {}: {:+.*}",
+                    md_a.symbol, md_b.symbol, dp.price, spread.raw
+                )),
+                Span::raw(" · "),
+                Span::styled(
+                    format!(
+                        "{} − β·{}: {:+.*}",
+                        md_a.symbol, md_b.symbol, dp.price, spread.hedged
+                    ),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" · β: {:.*}", dp.indicator, spread.beta)),
+            ]),
+            None => Line::from("no history yet"),
+        };
+    f.render_widget(Paragraph::new(text).block(panel(title, app)), area);
+}
+
+/// One-line order strip: pending orders (dimmed, with time to fill) first,
+/// then fills newest first. Whatever does not fit is cut off on the right.
+fn draw_orders(f: &mut Frame, app: &App, orders: &Blotter, md_vec: &[MarketData], area: Rect) {
+    let now = Instant::now();
+    let pending = orders.pending().map(|o| {
+        Span::styled(
+            format!(
+                "pending {} {} {} ({}ms)",
+                o.side,
+                o.qty,
+                md_vec[o.stock].symbol,
+                o.fill_at.saturating_duration_since(now).as_millis()
+            ),
+            Style::default()
+                .fg(app.theme.muted)
+                .add_modifier(Modifier::ITALIC),
+        )
+    });
+    let fills = orders.recent_fills(10).map(|fill| {
+        Span::raw(format!(
+            "filled {} {} {} @ {:.*} slip {:+.*}",
+            fill.side,
+            fill.qty,
+            md_vec[fill.stock].symbol,
+            app.decimals.price,
+            fill.price,
+            app.decimals.price,
+            fill.slippage()
+        ))
+    });
+
+    let mut spans = vec![];
+    for span in pending.chain(fills) {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" · "));
+        }
+        spans.push(span);
+    }
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).block(panel("Orders", app)),
+        area,
+    );
+}
+
+fn draw_alerts(f: &mut Frame, app: &App, alerts: &AlertLog, area: Rect) {
+    let rows = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = alerts
+        .recent(rows)
+        .map(|a| {
+            Line::from(format!(
+                "{:>5.1}s ago  {}",
+                a.at.elapsed().as_secs_f64(),
+                a.message
+            ))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines).block(panel("Alerts", app)), area);
+}
+
+fn draw_overview(
+    f: &mut Frame,
+    app: &App,
+    orders: &Blotter,
+    md_vec: &[MarketData],
+    ui_vec: &[UiData],
+    area: Rect,
+) {
+    let (md_pct, ui_pct) = match app.zoom {
+        Zoom::Split => app.layout,
+        Zoom::Backend => (100, 0),
+        Zoom::Frontend => (0, 100),
+    };
+    let chart_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(md_pct),
+            Constraint::Percentage(ui_pct),
+        ])
+        .split(area);
+
+    backend_chart(app, orders, md_vec, ui_vec, app.show_returns).render(f, app, chart_chunks[0]);
+    let mut cached = app.frontend_chart.borrow_mut();
+    if cached
+        .as_ref()
+        .is_none_or(|(version, _)| *version != app.frontend_version)
+    {
+        *cached = Some((app.frontend_version, frontend_chart(app, md_vec, ui_vec)));
+    }
+    if let Some((_, chart)) = cached.as_ref() {
+        chart.render(f, app, chart_chunks[1]);
+    }
+}
+
+/// The chart area tiled into panes; the focused one is marked in its title.
+fn draw_panes(
+    f: &mut Frame,
+    app: &App,
+    panes: &Pane,
+    orders: &Blotter,
+    md_vec: &[MarketData],
+    ui_vec: &[UiData],
+    area: Rect,
+) {
+    for (i, (rect, content)) in panes.layout(area).into_iter().enumerate() {
+        let mut chart = match content {
+            Content::Backend => backend_chart(app, orders, md_vec, ui_vec, app.show_returns),
+            Content::Returns => backend_chart(app, orders, md_vec, ui_vec, true),
+            Content::Frontend => frontend_chart(app, md_vec, ui_vec),
+            Content::Rsi => rsi_chart(app, md_vec),
+            Content::Candles => candle_chart(app, &md_vec[app.selected]),
+        };
+        if i == app.focus {
+            chart.title = format!("> {}", chart.title);
+        }
+        chart.render(f, app, rect);
+    }
+}
+
+/// Every stock's price (or per-tick return), optionally with the selected
+/// stock's frontend average on top.
+fn backend_chart(
+    app: &App,
+    orders: &Blotter,
+    md_vec: &[MarketData],
+    ui_vec: &[UiData],
+    returns: bool,
+) -> ChartSpec<'static> {
+    let axis = XAxis::of(app);
+    let window = axis.window(app);
+    // In returns mode each series becomes its per-tick % change instead
+    let plotted = |history: &[f64]| -> Vec<f64> {
+        if returns {
+            app.return_kind
+                .of(tail(history, window.saturating_add(1)))
+                .iter()
+                .map(|r| r * 100.0)
+                .collect()
+        } else {
+            tail(history, window).to_vec()
+        }
+    };
+    // Hidden stocks plot nothing, so they do not stretch the axis either
+    let md_series: Vec<Vec<f64>> = md_vec
+        .iter()
+        .map(|md| {
+            if md.hidden {
+                vec![]
+            } else {
+                plotted(&md.history)
+            }
+        })
+        .collect();
+    // The frontend keeps no timestamps, so it cannot go on a time axis
+    let overlay = (app.overlay_frontend && app.time_window.is_none())
+        .then(|| plotted(&ui_vec[app.selected].history));
+    let plotted_values = md_series.iter().chain(&overlay).flatten();
+    let (md_title, md_bounds) = if returns {
+        let max_abs = plotted_values.fold(0.0, |m: f64, v| m.max(v.abs()));
+        let bounds = y_bounds([-max_abs, max_abs].iter(), app.y_pad);
+        let title = match app.return_kind {
+            ReturnKind::Simple => "Backend Returns (%)",
+            ReturnKind::Log => "Backend Log Returns (%)",
+        };
+        (title, bounds)
+    } else {
+        ("Backend Stocks", y_bounds(plotted_values, app.y_pad))
+    };
+    let md_bounds = app.backend_y.bounds(md_bounds);
+    // Each stock is numbered by its own tick count, so with --stock-rates the
+    // slower ones end further left
+    let mut md_chart = ChartSpec::new(md_title.to_string(), app.plot_points, md_bounds);
+    match axis {
+        XAxis::Timed(_, window) => md_chart = md_chart.over_last(window),
+        XAxis::Ticks => {
+            let last_tick = md_vec.iter().map(|md| md.ticks).max().unwrap_or(0);
+            md_chart = md_chart.ending_at(last_tick);
+        }
+        XAxis::Relative => {}
+    }
+    let mut md_chart = md_chart.with_zero_line(app.theme.muted);
+    let selected = &md_vec[app.selected];
+    md_chart.push(closed_shading(
+        app,
+        axis,
+        selected,
+        md_series[app.selected].len(),
+        md_bounds,
+    ));
+    for (md, values) in md_vec.iter().zip(&md_series) {
+        let series = Series::new(axis.points(values, md), symbols::Marker::Dot)
+            .name(format!("Backend {}", md.symbol));
+        md_chart.push(faded_series(series, app.theme.series(md.count), app.fade));
+        if !returns {
+            md_chart.push(trade_markers(app, axis, orders, md, values.len()));
+            push_band(&mut md_chart, app, md);
+        }
+        if md.count == app.selected {
+            md_chart.push(crosshair(app, axis, md, values.len(), md_bounds));
+            if !returns {
+                md_chart.push(vwap_bands(app, axis, md, values.len()));
+            }
+        }
+    }
+    if let Some(values) = &overlay {
+        let style = Style::default()
+            .fg(app.theme.series(app.selected))
+            .add_modifier(Modifier::DIM);
+        let selected = &md_vec[app.selected];
+        let series = Series::new(axis.points(values, selected), symbols::Marker::Braille)
+            .name(format!("Frontend {}", selected.symbol))
+            .style(style)
+            .line();
+        md_chart.push([series]);
+    }
+    if app.log_scale && !returns {
+        md_chart = md_chart.log_y(app.decimals.price);
+    }
+    md_chart
+}
+
+/// ▲ / ▼ at the tick and fill price of the stock's last few trades that fall
+/// inside its plotted window of `len` samples, matching `points` in
+/// `backend_chart` for both x-axis modes.
+fn trade_markers(
+    app: &App,
+    axis: XAxis,
+    orders: &Blotter,
+    md: &MarketData,
+    len: usize,
+) -> Vec<Series<'static>> {
+    let mut buys = vec![];
+    let mut sells = vec![];
+    for fill in orders.recent_fills_of(md.count, TRADE_MARKERS) {
+        let age = md.ticks.saturating_sub(fill.tick);
+        if age as usize >= len {
+            continue;
+        }
+        let Some(x) = axis.tick_x(md, len, age) else {
+            continue;
+        };
+        match fill.side {
+            Side::Buy => buys.push((x, fill.price)),
+            Side::Sell => sells.push((x, fill.price)),
+        }
+    }
+    let (up, down) = if app.ascii {
+        ("^", "v")
+    } else {
+        ("▲", "▼")
+    };
+    [(buys, up, app.theme.gain), (sells, down, app.theme.loss)]
+        .into_iter()
+        .filter(|(points, _, _)| !points.is_empty())
+        .map(|(points, glyph, color)| {
+            Series::new(points, symbols::Marker::Dot)
+                .glyph(glyph)
+                .style(Style::default().fg(color))
+        })
+        .collect()
+}
+
+/// How a backend chart numbers its x-axis.
+#[derive(Clone, Copy)]
+enum XAxis {
+    /// 0..plot_points, newest on the right.
+    Relative,
+    /// Absolute tick numbers (`x`).
+    Ticks,
+    /// Seconds before a moment, over a window (`--history-seconds`).
+    Timed(Instant, Duration),
+}
+
+impl XAxis {
+    fn of(app: &App) -> Self {
+        if let Some(window) = app.time_window {
+            XAxis::Timed(app.held_at.unwrap_or_else(Instant::now), window)
+        } else if app.absolute_x {
+            XAxis::Ticks
+        } else {
+            XAxis::Relative
+        }
+    }
+
+    /// Most recent samples plotted: all of them against time.
+    fn window(self, app: &App) -> usize {
+        match self {
+            XAxis::Timed(..) => usize::MAX,
+            _ => app.plot_points,
+        }
+    }
+
+    fn points(self, values: &[f64], md: &MarketData) -> Vec<(f64, f64)> {
+        match self {
+            XAxis::Relative => to_points(values),
+            XAxis::Ticks => to_points_ending_at(values, md.ticks),
+            XAxis::Timed(now, _) => to_points_timed(values, &md.times, now),
+        }
+    }
+
+    /// Chart x of the tick `age` ticks before the newest of `md`, whose last
+    /// `len` values are plotted; `None` once its timestamp is evicted.
+    fn tick_x(self, md: &MarketData, len: usize, age: u64) -> Option<f64> {
+        match self {
+            XAxis::Relative => Some((len - 1) as f64 - age as f64),
+            XAxis::Ticks => Some(md.ticks.saturating_sub(age) as f64),
+            XAxis::Timed(now, _) => {
+                let i = md.times.len().checked_sub(age as usize + 1)?;
+                Some(-now.saturating_duration_since(md.times[i]).as_secs_f64())
+            }
+        }
+    }
+}
+
+/// Points filling a vertical line at `x` from `low` to `high`.
+fn column(x: f64, (low, high): (f64, f64)) -> impl Iterator<Item = (f64, f64)> {
+    const ROWS: usize = 96;
+    (0..=ROWS).map(move |r| (x, low + (high - low) * r as f64 / ROWS as f64))
+}
+
+/// Vertical line at the crosshair, placed on the ticks of `md`.
+fn crosshair(
+    app: &App,
+    axis: XAxis,
+    md: &MarketData,
+    len: usize,
+    bounds: (f64, f64),
+) -> Option<Series<'static>> {
+    let age = app.crosshair.filter(|&age| age < len)?;
+    let points: Vec<_> = column(axis.tick_x(md, len, age as u64)?, bounds).collect();
+    Some(Series::new(points, symbols::Marker::Braille).style(Style::default().fg(app.theme.muted)))
+}
+
+/// Muted columns behind the ticks of `md` spent outside the session, the
+/// last `len` of them being on screen.
+fn closed_shading(
+    app: &App,
+    axis: XAxis,
+    md: &MarketData,
+    len: usize,
+    (low, high): (f64, f64),
+) -> Option<Series<'static>> {
+    let mut points = vec![];
+    for age in 0..len as u64 {
+        let Some(tick) = md.ticks.checked_sub(age) else {
+            break;
+        };
+        if !md.closed.contains(tick) {
+            continue;
+        }
+        if let Some(x) = axis.tick_x(md, len, age) {
+            points.extend(column(x, (low, high)));
+        }
+    }
+    (!points.is_empty()).then(|| {
+        Series::new(points, symbols::Marker::Braille).style(
+            Style::default()
+                .fg(app.theme.muted)
+                .add_modifier(Modifier::DIM),
+        )
+    })
+}
+
+fn frontend_chart(app: &App, md_vec: &[MarketData], ui_vec: &[UiData]) -> ChartSpec<'static> {
+    let shown = || ui_vec.iter().filter(|ui| !md_vec[ui.count].hidden);
+    let ui_bounds = app.frontend_y.bounds(y_bounds(
+        shown().flat_map(|x| tail(&x.history, app.plot_points)),
+        app.y_pad,
+    ));
+    let mut ui_chart = ChartSpec::new(
+        format!(
+            "Frontend · {} ({})",
+            app.frontend_model.label(),
+            app.frontend_lag
+        ),
+        app.plot_points,
+        ui_bounds,
+    )
+    .with_zero_line(app.theme.muted);
+    for ui in shown() {
+        let series = Series::new(
+            to_points(tail(&ui.history, app.plot_points)),
+            symbols::Marker::Braille,
+        )
+        .name(format!("Frontend {}", md_vec[ui.count].symbol));
+        ui_chart.push(faded_series(series, app.theme.series(ui.count), app.fade));
+    }
+    if app.log_scale {
+        ui_chart = ui_chart.log_y(app.decimals.avg);
+    }
+    ui_chart
+}
+
+/// The selected stock's plotted window as `app.candle_window`-tick candles:
+/// a thin wick from low to high under a solid open-to-close body, coloured
+/// by direction. Candles start on tick numbers divisible by the window so
+/// they keep their boundaries as the history scrolls. A `--replay-ohlc`
+/// stock shows its bars one candle each instead.
+fn candle_chart(app: &App, md: &MarketData) -> ChartSpec<'static> {
+    let (title, candles) = match md.bars.last() {
+        Some(last) => {
+            let bars = &md.bars[md.bars.len().saturating_sub(app.plot_points)..];
+            let candles: Vec<Candle> = bars.iter().map(|bar| bar.candle).collect();
+            (
+                format!("Candles {} · bars to {}", md.symbol, last.time),
+                candles,
+            )
+        }
+        None => {
+            let window = app.candle_window;
+            let values = tail(md.samples(), app.plot_points);
+            let first_tick = (md.ticks + 1).saturating_sub(values.len() as u64);
+            let skip = (window as u64 - first_tick % window as u64) % window as u64;
+            let values = &values[(skip as usize).min(values.len())..];
+            let title = format!("Candles {} · {} ticks", md.symbol, window);
+            (title, indicators::to_candles(values, window))
+        }
+    };
+
+    let bounds = y_bounds(candles.iter().flat_map(|c| [&c.low, &c.high]), app.y_pad);
+    let mut chart = ChartSpec::new(title, candles.len().max(1), bounds);
+    for (i, candle) in candles.iter().enumerate() {
+        let x = i as f64 + 0.5;
+        let color = if candle.close >= candle.open {
+            app.theme.gain
+        } else {
+            app.theme.loss
+        };
+        let wick: Vec<_> = column(x, (candle.low, candle.high)).collect();
+        let body: Vec<_> = column(
+            x,
+            (candle.open.min(candle.close), candle.open.max(candle.close)),
+        )
+        .collect();
+        chart.push([
+            Series::new(wick, symbols::Marker::Braille).style(Style::default().fg(color)),
+            Series::new(body, symbols::Marker::Block).style(Style::default().fg(color)),
+        ]);
+    }
+    chart
+}
+
+/// As many stocks' `candle_chart`s as fit at `CANDLE_CELL` or larger, one
+/// page of them at a time under a header line naming the page.
+fn draw_candle_grid(f: &mut Frame, app: &App, md_vec: &[MarketData], area: Rect) {
+    let (min_width, min_height) = CANDLE_CELL;
+    let rows = ((area.height.saturating_sub(1)) / min_height).max(1) as usize;
+    let cols = (area.width / min_width).max(1) as usize;
+    let per_page = rows * cols;
+    let md_vec: Vec<&MarketData> = md_vec.iter().filter(|md| !md.hidden).collect();
+    let pages = md_vec.len().div_ceil(per_page).max(1);
+    app.candle_pages.set(pages);
+    let page = app.candle_page.min(pages - 1);
+    let shown = &md_vec[page * per_page..md_vec.len().min((page + 1) * per_page)];
+    // Only as many cells as this page fills, so they grow to use the room
+    let cols = cols.min(shown.len()).max(1);
+    let rows = shown.len().div_ceil(cols).max(1);
+
+    let [header, grid] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .areas(area);
+    let mut title = format!("Candles · {} ticks each", app.candle_window);
+    if pages > 1 {
+        title.push_str(&format!(" · page {}/{} (PgUp/PgDn)", page + 1, pages));
+    }
+    f.render_widget(
+        Paragraph::new(title).style(Style::default().fg(app.theme.muted)),
+        header,
+    );
+    let row_areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(grid);
+    for (row, stocks) in row_areas.iter().zip(shown.chunks(cols)) {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, cols as u32); cols])
+            .split(*row);
+        for (cell, md) in cells.iter().zip(stocks) {
+            candle_chart(app, md).render(f, app, *cell);
+        }
+    }
+}
+
+/// RSI of every stock at each plotted tick, on a fixed 0..100 axis.
+fn rsi_chart(app: &App, md_vec: &[MarketData]) -> ChartSpec<'static> {
+    let title = format!("RSI ({})", indicators::RSI_PERIOD);
+    let mut chart = ChartSpec::new(title, app.plot_points, (0.0, 100.0));
+    for md in md_vec {
+        // Only real ticks count towards the warm-up, not the seed padding
+        let seed = md.history.len() - md.samples().len();
+        let start = md.history.len().saturating_sub(app.plot_points);
+        let points: Vec<(f64, f64)> = (start..md.history.len())
+            .filter(|&end| end >= seed)
+            .filter_map(|end| {
+                let rsi = indicators::rsi(&md.history[seed..=end], indicators::RSI_PERIOD)?;
+                Some(((end - start) as f64, rsi))
+            })
+            .collect();
+        let current = indicators::rsi(md.samples(), indicators::RSI_PERIOD);
+        let series = Series::new(points, symbols::Marker::Braille)
+            .name(format!(
+                "RSI {} {}",
+                md.symbol,
+                or_dashes(current, app.decimals.percent)
+            ))
+            .style(Style::default().fg(app.theme.series(md.count)))
+            .line();
+        chart.push([series]);
+    }
+    chart
+}
+
+/// Selected stock on its own; with the multi-timeframe view the last
+/// `SHORT_WINDOW_LEN` ticks are shown next to the plotted history.
+fn draw_detail(f: &mut Frame, app: &App, orders: &Blotter, md: &MarketData, area: Rect) {
+    let axis = XAxis::of(app);
+    let mut windows = vec![(tail(&md.history, axis.window(app)), app.plot_points, axis)];
+    if app.multi_timeframe {
+        // Always by tick: a time axis would squeeze the short window flat
+        let short_axis = if app.absolute_x {
+            XAxis::Ticks
+        } else {
+            XAxis::Relative
+        };
+        windows.insert(
+            0,
+            (
+                tail(&md.history, SHORT_WINDOW_LEN),
+                SHORT_WINDOW_LEN,
+                short_axis,
+            ),
+        );
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Ratio(1, windows.len() as u32);
+            windows.len()
+        ])
+        .split(area);
+
+    for ((slice, x_len, axis), chunk) in windows.into_iter().zip(chunks.iter()) {
+        let returns = app.return_kind.of(slice);
+        let label = if md.name.is_empty() {
+            md.symbol.clone()
+        } else {
+            format!("{} ({})", md.symbol, md.name)
+        };
+        let title = format!(
+            "{} · last {} ticks · RSI {} · skew {:.*} · kurt {:.*} · accel {:+.*e}",
+            label,
+            slice.len(),
+            or_dashes(
+                indicators::rsi(md.samples(), indicators::RSI_PERIOD),
+                app.decimals.percent
+            ),
+            app.decimals.indicator,
+            indicators::skewness(&returns),
+            app.decimals.indicator,
+            indicators::kurtosis(&returns),
+            app.decimals.indicator,
+            indicators::acceleration(slice)
+        );
+        let bounds = y_bounds(slice.iter(), app.y_pad);
+        let mut chart = ChartSpec::new(title, x_len, bounds);
+        match axis {
+            XAxis::Timed(_, window) => chart = chart.over_last(window),
+            XAxis::Ticks => chart = chart.ending_at(md.ticks),
+            XAxis::Relative => {}
+        }
+        let mut chart = chart.with_zero_line(app.theme.muted);
+        chart.push(closed_shading(app, axis, md, slice.len(), bounds));
+        chart.push(crosshair(app, axis, md, slice.len(), bounds));
+        let series = Series::new(axis.points(slice, md), symbols::Marker::Braille)
+            .name(format!("Backend {}", md.symbol))
+            .line();
+        chart.push(faded_series(series, app.theme.series(md.count), app.fade));
+        chart.push(trade_markers(app, axis, orders, md, slice.len()));
+        chart.push(vwap_bands(app, axis, md, slice.len()));
+        push_band(&mut chart, app, md);
+        if app.log_scale {
+            chart = chart.log_y(app.decimals.price);
+        }
+        chart.render(f, app, *chunk);
+    }
+}
+
+/// `--vwap-bands` over the last `len` plotted samples of `md`: the VWAP in
+/// its colour and the bands dimmed, each point taken over the
+/// `VWAP_WINDOW` real ticks up to it.
+fn vwap_bands(app: &App, axis: XAxis, md: &MarketData, len: usize) -> Vec<Series<'static>> {
+    let Some(k) = app.vwap_bands else {
+        return vec![];
+    };
+    let n = md.history.len();
+    let seed = n - md.samples().len();
+    let mut lines: [Vec<(f64, f64)>; 3] = Default::default();
+    for age in (0..len).rev() {
+        let end = n - age;
+        let start = end.saturating_sub(indicators::VWAP_WINDOW).max(seed);
+        let Some(x) = axis.tick_x(md, len, age as u64) else {
+            continue;
+        };
+        if end < start + 2 {
+            continue;
+        }
+        let (lower, vwap, upper) =
+            indicators::vwap_bands(&md.history[start..end], &md.volumes[start..end], k);
+        for (line, y) in lines.iter_mut().zip([lower, vwap, upper]) {
+            line.push((x, y));
+        }
+    }
+    let color = app.theme.series(md.count);
+    let band = Style::default().fg(color).add_modifier(Modifier::DIM);
+    let [lower, vwap, upper] = lines;
+    vec![
+        Series::new(lower, symbols::Marker::Braille)
+            .style(band)
+            .line(),
+        Series::new(upper, symbols::Marker::Braille)
+            .style(band)
+            .line(),
+        Series::new(vwap, symbols::Marker::Braille)
+            .name(format!("VWAP ±{}σ", k))
+            .style(Style::default().fg(color))
+            .line(),
+    ]
+}
+
+/// `--price-bands` edges of `md`, dimmed in its colour.
+fn push_band(chart: &mut ChartSpec, app: &App, md: &MarketData) {
+    if let Some(band) = md.band {
+        let style = Style::default()
+            .fg(app.theme.series(md.count))
+            .add_modifier(Modifier::DIM);
+        chart.push_level(band.low, style);
+        chart.push_level(band.high, style);
+    }
+}
+
+/// `SYM price ±% | …` for every stock, wrapped around and shifted left by
+/// `offset` characters so it scrolls as the offset grows.
+fn draw_ticker(f: &mut Frame, app: &App, offset: usize, md_vec: &[MarketData], area: Rect) {
+    let dp = app.decimals;
+    let mut tape: Vec<(char, Style)> = vec![];
+    for md in md_vec.iter().filter(|md| !md.hidden) {
+        let price = *md.price.read().unwrap();
+        let change = indicators::pct_change(&md.history, app.change_lookback);
+        let quote = format!("{} {:.*} ", md.symbol, dp.price, price);
+        tape.extend(quote.chars().map(|c| (c, Style::default())));
+        let style = Style::default().fg(app.theme.change(change));
+        let move_pct = format!("{:+.*}%", dp.percent, change);
+        tape.extend(move_pct.chars().map(|c| (c, style)));
+        let muted = Style::default().fg(app.theme.muted);
+        tape.extend(" | ".chars().map(|c| (c, muted)));
+    }
+    if tape.is_empty() {
+        return;
+    }
+    let spans: Vec<Span> = (0..area.width as usize)
+        .map(|i| {
+            let (c, style) = tape[(offset + i) % tape.len()];
+            Span::styled(c.to_string(), style)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Suggests a better `--plot-points` when the classic backend chart spreads
+/// one tick over many columns or squeezes many ticks into one.
+fn density_hint(app: &App, charts: Rect) -> Option<String> {
+    if app.density_warning <= 0.0
+        || app.view != View::Overview
+        || app.panes.is_some()
+        || app.time_window.is_some()
+        || app.zoom == Zoom::Frontend
+    {
+        return None;
+    }
+    let percent = if app.zoom == Zoom::Backend {
+        100
+    } else {
+        app.layout.0
+    };
+    // Inside the borders
+    let columns = (charts.width as usize * percent as usize / 100).saturating_sub(2);
+    if columns == 0 {
+        return None;
+    }
+    let per_column = app.plot_points as f64 / columns as f64;
+    if per_column > app.density_warning {
+        Some(format!(
+            "{:.0} ticks/col: try fewer --plot-points",
+            per_column
+        ))
+    } else if per_column * app.density_warning < 1.0 {
+        Some(format!(
+            "{:.0} cols/tick: try more --plot-points",
+            1.0 / per_column
+        ))
+    } else {
+        None
+    }
+}
+
+fn draw_footer(
+    f: &mut Frame,
+    app: &App,
+    sim: &SimControl,
+    md_vec: &[MarketData],
+    density: Option<String>,
+    area: Rect,
+) {
+    let hints = if app.mode == Mode::Command {
+        format!(" :{}_   Enter runs · Esc cancels", app.command)
+    } else {
+        format!(" {}", keys::footer_hints())
+    };
+
+    let mut status = vec![];
+    status.extend(app.notice().map(str::to_string));
+    status.extend(density);
+    if sim.replaying {
+        status.push(format!("[ ] replay speed: {}×", sim.replay_speed));
+    }
+    if sim.paused {
+        status.push("PAUSED (. step)".to_string());
+    }
+    if let Some(tick) = app.playback {
+        status.push(format!(
+            "playback {}/{} ({:.1}%)",
+            tick,
+            app.playback_len,
+            tick as f64 / app.playback_len.max(1) as f64 * 100.0
+        ));
+    } else if app.inspecting() {
+        status.push("INSPECTING (Esc resumes)".to_string());
+    }
+    if let Some(scenario) = sim.scenario {
+        status.push(format!("scenario: {}", scenario));
+    }
+    status.push(format!("vol: {}×", sim.vol));
+    if let Some(session) = sim.session {
+        status.push(session.to_string());
+    }
+    if sim.fixed_seed {
+        status.push(format!("fixed seed {}", sim.seed));
+    } else {
+        status.push(format!("seed {}", sim.seed));
+    }
+    let total_ticks: u64 = md_vec.iter().map(|md| md.ticks).sum();
+    status.push(format!(
+        "Δ alarm > {:.*}",
+        app.decimals.price, app.diff_threshold
+    ));
+    status.push(format!("{:.0} fps", app.pacer.fps));
+    status.push(format!("unit: {}", app.time_unit.label()));
+    status.push(app.zone.clock(SystemTime::now()));
+    status.push(format!("up {}", format_hms(app.start.elapsed())));
+    status.push(format!("{} ticks ", total_ticks));
+    let status = status.join(" · ");
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(display_width(&status) as u16),
+        ])
+        .split(area);
+    let style = Style::default().fg(app.theme.muted);
+    f.render_widget(Paragraph::new(hints).style(style), chunks[0]);
+    f.render_widget(Paragraph::new(status).style(style), chunks[1]);
+}
+
+/// Return correlation matrix as a heat grid over the middle of the charts.
+fn draw_correlation(
+    f: &mut Frame,
+    app: &App,
+    matrix: &[Vec<f64>],
+    md_vec: &[MarketData],
+    area: Rect,
+) {
+    // Two spaces of gap, the sign and "1." around the decimals
+    let cell = app.decimals.indicator + 5;
+    let label = md_vec
+        .iter()
+        .map(|md| md.symbol.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut header = format!("{:label$}", "");
+    for md in md_vec {
+        header.push_str(&format!("{:>cell$}", md.symbol));
+    }
+    let mut lines = vec![Line::from(header)];
+    for (md, row) in md_vec.iter().zip(matrix) {
+        let mut spans = vec![Span::raw(format!("{:label$}", md.symbol))];
+        spans.extend(row.iter().map(|&r| {
+            Span::styled(
+                format!("{:>+cell$.*}", app.decimals.indicator, r),
+                Style::default()
+                    .fg(app.theme.heat_text(r))
+                    .bg(app.theme.heat_color(r)),
+            )
+        }));
+        lines.push(Line::from(spans));
+    }
+
+    let width = (label + cell * md_vec.len() + 2) as u16;
+    let area = centered_rect(width, lines.len() as u16 + 2, area);
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(panel("Correlation", app)), area);
+}
+
+/// Every key binding with its description, straight from the keymap.
+fn draw_help(f: &mut Frame, app: &App) {
+    let width = keys::BINDINGS
+        .iter()
+        .map(|b| b.key.to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = keys::BINDINGS
+        .iter()
+        .map(|b| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>width$}  ", b.key.to_string()),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(b.description),
+            ])
+        })
+        .collect();
+    let text_width = keys::BINDINGS
+        .iter()
+        .map(|b| display_width(b.description))
+        .max()
+        .unwrap_or(0);
+    let area = centered_rect(
+        (width + 2 + text_width + 2) as u16,
+        lines.len() as u16 + 2,
+        f.area(),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).block(panel("Keys (? to close)", app)),
+        area,
+    );
+}
+
+/// An indicator value, or `--` while it is still warming up.
+fn or_dashes(value: Option<f64>, precision: usize) -> String {
+    value.map_or("--".to_string(), |v| format!("{:.*}", precision, v))
+}
+
+/// Single-line message in a bordered box over the middle of the screen.
+fn draw_popup(f: &mut Frame, app: &App, message: &str) {
+    let area = centered_rect(display_width(message) as u16 + 4, 3, f.area());
+    f.render_widget(Clear, area);
+    let text = Paragraph::new(message).alignment(Alignment::Center);
+    f.render_widget(text.block(panel("", app)), area);
+}
+
+/// A `width` x `height` rect centred in `area`, shrunk to fit if needed.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// A duration in `unit`; `Auto` picks µs below a millisecond, ms below a
+/// second and s above.
+fn format_duration(d: Duration, unit: TimeUnit) -> String {
+    let unit = match unit {
+        TimeUnit::Auto if d < Duration::from_millis(1) => TimeUnit::Micros,
+        TimeUnit::Auto if d < Duration::from_secs(1) => TimeUnit::Millis,
+        TimeUnit::Auto => TimeUnit::Secs,
+        fixed => fixed,
+    };
+    match unit {
+        TimeUnit::Micros => format!("{}µs", d.as_micros()),
+        TimeUnit::Millis => format!("{:.1}ms", d.as_secs_f64() * 1e3),
+        TimeUnit::Secs | TimeUnit::Auto => format!("{:.2}s", d.as_secs_f64()),
+    }
+}
+
+fn format_hms(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Bordered block used by every panel; plain `+-|` borders in ASCII mode,
+/// and the title through `title_for`.
+pub fn panel<'a>(title: impl Into<Line<'a>>, app: &App) -> Block<'a> {
+    let mut title: Line = title.into();
+    for span in &mut title.spans {
+        if let Cow::Owned(plain) = title_for(app, &span.content) {
+            span.content = plain.into();
+        }
+    }
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if app.ascii {
+        block.border_set(ASCII_BORDER)
+    } else {
+        block
+    }
+}
+
+/// `label` as this terminal can draw it in a title. With `plain_titles`,
+/// symbols beyond ASCII (which some Windows consoles draw as boxes, or at
+/// a width other than the one measured, pushing the border out of line)
+/// become ASCII stand-ins, and anything without one a `?`.
+pub fn title_for<'a>(app: &App, label: &'a str) -> Cow<'a, str> {
+    if !app.plain_titles || label.is_ascii() {
+        return Cow::Borrowed(label);
+    }
+    let mut plain = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            c if c.is_ascii() => plain.push(c),
+            '·' | '—' | '–' => plain.push('-'),
+            'Δ' => plain.push('D'),
+            'σ' => plain.push_str("sd"),
+            'β' => plain.push('b'),
+            '±' => plain.push_str("+/-"),
+            '×' => plain.push('x'),
+            '→' => plain.push_str("->"),
+            '←' => plain.push_str("<-"),
+            '…' => plain.push_str("..."),
+            // Variation selectors and joiners have no width of their own
+            '\u{fe0f}' | '\u{200d}' => {}
+            _ => plain.push('?'),
+        }
+    }
+    Cow::Owned(plain)
+}
+
+/// Cells `text` takes up on screen, wide characters such as emoji counting
+/// two, which is what the layout has to reserve for it.
+fn display_width(text: &str) -> usize {
+    Span::raw(text).width()
+}
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};